@@ -9,11 +9,20 @@ use quick_xml::DeError;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use time::macros::format_description;
+use time::Date;
 
 use crate::api::gradebook::GradebookError;
 use crate::crypto::{AuthToken, CryptoError};
 use crate::get_edu_version;
 
+pub(crate) mod analytics;
+pub(crate) mod assessments;
+pub(crate) mod assignments;
+pub(crate) mod calendar;
+pub(crate) mod children;
+pub(crate) mod course_history;
+
 #[cfg(feature = "attendance")]
 pub(crate) mod attendance;
 
@@ -24,7 +33,16 @@ pub(crate) mod cache;
 pub(crate) mod schedule;
 
 pub(crate) mod documents;
+pub(crate) mod fees;
 pub(crate) mod gradebook;
+pub(crate) mod health;
+pub(crate) mod media;
+pub(crate) mod memo;
+pub(crate) mod messages;
+pub(crate) mod notes;
+pub(crate) mod redaction;
+pub(crate) mod report_cards;
+pub(crate) mod sanitize;
 pub(crate) mod school_info;
 
 #[cfg(feature = "enhanced")]
@@ -32,6 +50,28 @@ mod scraper;
 
 pub(crate) mod student_info;
 
+/// Parses the `M/D/YYYY` dates StudentVue uses, ignoring any trailing time component.
+/// ISO `YYYY-MM-DD` dates, as clients send them, are accepted too.
+pub fn parse_date(date: &str) -> Option<Date> {
+    let date = date.split_whitespace().next()?;
+    Date::parse(
+        date,
+        format_description!("[month padding:none]/[day padding:none]/[year]"),
+    )
+    .or_else(|_| Date::parse(date, format_description!("[year]-[month]-[day]")))
+    .ok()
+}
+
+/// Escapes client input before it's placed in the XML params sent to StudentVue.
+pub fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 pub fn base64_mangle<T: std::error::Error>(inp: T) -> String {
     BASE64_STANDARD.encode(inp.to_string().as_bytes())
 }
@@ -72,13 +112,23 @@ pub enum ApiError {
     ExpiredKey,
     #[error("Security failed - do you have a user agent?")]
     NoSecureResponse,
+    #[error("Invalid query parameters")]
+    InvalidQuery,
+    #[error("Not found")]
+    NotFound,
+    #[error("Unable to process image")]
+    Image,
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         let code = match &self {
-            ApiError::StudentVue(_) | ApiError::EmptyCredentials => StatusCode::BAD_REQUEST,
+            ApiError::StudentVue(_) | ApiError::EmptyCredentials | ApiError::InvalidQuery => {
+                StatusCode::BAD_REQUEST
+            }
             ApiError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Gradebook(GradebookError::UnknownPeriod(_)) => StatusCode::BAD_REQUEST,
             ApiError::Crypto(crypto) => match crypto {
                 CryptoError::InvalidCipher(_) | CryptoError::CryptError(_) => {
                     StatusCode::BAD_REQUEST
@@ -248,6 +298,8 @@ impl ProcessWebServiceRequest {
     fn up_default(
         user_id: String,
         password: String,
+        parent: bool,
+        child: u32,
         method_name: String,
         params: String,
     ) -> ProcessWebServiceRequest {
@@ -256,10 +308,10 @@ impl ProcessWebServiceRequest {
             user_id,
             password,
             skip_login_log: "1".to_string(),
-            parent: "0".to_string(),
+            parent: if parent { "1" } else { "0" }.to_string(),
             web_service_handle_name: "PXPWebServices".to_string(),
             method_name,
-            param_str: format!("<Parms><ChildIntID>0</ChildIntID>{params}</Parms>"),
+            param_str: format!("<Parms><ChildIntID>{child}</ChildIntID>{params}</Parms>"),
         }
     }
 
@@ -271,6 +323,8 @@ impl ProcessWebServiceRequest {
         ProcessWebServiceRequest::up_default(
             token.username.clone(),
             token.password.clone(),
+            token.parent,
+            token.child_id(),
             method_name,
             params,
         )
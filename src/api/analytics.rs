@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::gradebook::{
+    letter_grade, Assignment, Category, Class, Response, ScoreStatus, LETTER_SCALE,
+};
+
+pub fn missing(gb: &Response) -> MissingReport {
+    MissingReport {
+        report_period: gb.report_period,
+        classes: gb
+            .classes
+            .iter()
+            .filter_map(|class| {
+                let assignments: Vec<MissingAssignment> = class
+                    .assignments
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, a)| a.status.is_outstanding())
+                    .map(|(idx, a)| {
+                        let full = class.grade_with(idx, a.points_possible);
+                        let zero = class.grade_with(idx, 0.0);
+
+                        MissingAssignment {
+                            name: a.name.clone(),
+                            kind: a.kind.clone(),
+                            due_date: a.due_date.clone(),
+                            status: a.status,
+                            points_possible: a.points_possible,
+                            impact: full.zip(zero).map(|(f, z)| f - z).unwrap_or_default(),
+                        }
+                    })
+                    .collect();
+
+                if assignments.is_empty() {
+                    return None;
+                }
+
+                Some(MissingClass {
+                    name: class.name.clone(),
+                    teacher: class.teacher.clone(),
+                    grade: class.grade,
+                    letter_grade: class.letter_grade.clone(),
+                    assignments,
+                })
+            })
+            .collect(),
+    }
+}
+
+pub fn class_detail(gb: &Response, id: &str) -> Option<ClassDetail> {
+    let class = gb.classes.iter().find(|c| c.id == id)?;
+
+    let category_grades = class
+        .categories
+        .iter()
+        .filter(|(_, c)| c.points_possible > 0.0)
+        .map(|(name, c)| (name.clone(), c.points_earned / c.points_possible * 100.0))
+        .collect();
+
+    let by_impact = |a: &&Assignment| a.impact.unwrap_or_default();
+    let graded = class.assignments.iter().filter(|a| a.impact.is_some());
+
+    Some(ClassDetail {
+        report_period: gb.report_period,
+        analytics: ClassAnalytics {
+            computed_grade: class.compute_grade(&class.assignments),
+            category_grades,
+            outstanding: class
+                .assignments
+                .iter()
+                .filter(|a| a.status.is_outstanding())
+                .count(),
+            most_helpful: graded
+                .clone()
+                .max_by(|a, b| by_impact(a).total_cmp(&by_impact(b)))
+                .filter(|a| by_impact(a) > 0.0)
+                .map(|a| a.name.clone()),
+            most_harmful: graded
+                .min_by(|a, b| by_impact(a).total_cmp(&by_impact(b)))
+                .filter(|a| by_impact(a) < 0.0)
+                .map(|a| a.name.clone()),
+        },
+        class: class.clone(),
+    })
+}
+
+/// Compares two reporting periods class by class, matching classes by course id.
+pub fn compare(from: &Response, to: &Response) -> Comparison {
+    let mut ids: Vec<&str> = to.classes.iter().map(|c| c.id.as_str()).collect();
+    for class in &from.classes {
+        if !ids.contains(&class.id.as_str()) {
+            ids.push(&class.id);
+        }
+    }
+
+    let graded = |c: &Class| (c.letter_grade != "N/A" && c.grade.is_finite()).then_some(c.grade);
+    let percent = |c: &Category| {
+        (c.points_possible > 0.0).then(|| c.points_earned / c.points_possible * 100.0)
+    };
+
+    let classes = ids
+        .into_iter()
+        .map(|id| {
+            let old = from.classes.iter().find(|c| c.id == id);
+            let new = to.classes.iter().find(|c| c.id == id);
+
+            let from_grade = old.and_then(graded);
+            let to_grade = new.and_then(graded);
+            let from_letter = old
+                .filter(|c| graded(c).is_some())
+                .map(|c| c.letter_grade.clone());
+            let to_letter = new
+                .filter(|c| graded(c).is_some())
+                .map(|c| c.letter_grade.clone());
+
+            let mut names: Vec<&String> = Vec::new();
+            for name in old.into_iter().chain(new).flat_map(|c| c.categories.keys()) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+
+            let categories = names
+                .into_iter()
+                .map(|name| {
+                    let before = old.and_then(|c| c.categories.get(name));
+                    let after = new.and_then(|c| c.categories.get(name));
+                    let points = |c: Option<&Category>| {
+                        c.map_or((0.0, 0.0), |c| (c.points_earned, c.points_possible))
+                    };
+
+                    let shift = CategoryShift {
+                        weight: after.or(before).map(|c| c.weight).unwrap_or_default(),
+                        points_earned: points(after).0 - points(before).0,
+                        points_possible: points(after).1 - points(before).1,
+                        percent: before
+                            .and_then(percent)
+                            .zip(after.and_then(percent))
+                            .map(|(b, a)| a - b),
+                    };
+                    (name.clone(), shift)
+                })
+                .collect();
+
+            ClassComparison {
+                id: id.to_string(),
+                name: new.or(old).map(|c| c.name.clone()).unwrap_or_default(),
+                delta: from_grade.zip(to_grade).map(|(f, t)| t - f),
+                from_grade,
+                to_grade,
+                letter_changed: from_letter.is_some()
+                    && to_letter.is_some()
+                    && from_letter != to_letter,
+                from_letter,
+                to_letter,
+                categories,
+            }
+        })
+        .collect();
+
+    Comparison {
+        from: from.report_period,
+        to: to.report_period,
+        classes,
+    }
+}
+
+/// Projects a combined grade (semester or final) for every class that appears in `books`, one
+/// gradebook per marking period, in the same order as `policy.weights`.
+pub fn project(books: &[Response], policy: &Policy, exam: Option<f32>) -> Projection {
+    let mut ids: Vec<&str> = Vec::new();
+    for class in books.iter().flat_map(|b| &b.classes) {
+        if !ids.contains(&class.id.as_str()) {
+            ids.push(&class.id);
+        }
+    }
+
+    let classes = ids
+        .into_iter()
+        .map(|id| {
+            let found: Vec<_> = books
+                .iter()
+                .map(|b| b.classes.iter().find(|c| c.id == id))
+                .collect();
+
+            let name = found
+                .iter()
+                .flatten()
+                .next()
+                .map(|c| c.name.clone())
+                .unwrap_or_default();
+            let graded: Vec<_> = found
+                .iter()
+                .map(|c| c.filter(|c| c.letter_grade != "N/A" && c.grade.is_finite()))
+                .collect();
+            let grades: Vec<Option<f32>> = graded.iter().map(|c| c.map(|c| c.grade)).collect();
+            let letters: Vec<Option<String>> = graded
+                .iter()
+                .map(|c| c.map(|c| c.letter_grade.clone()))
+                .collect();
+
+            // each component is (weight, value), value being a percent or letter points
+            let components: Vec<(f32, f32)> = match policy.method {
+                Method::Percent => grades
+                    .iter()
+                    .zip(&policy.weights)
+                    .filter_map(|(g, w)| g.map(|g| (*w, g)))
+                    .collect(),
+                Method::Points => letters
+                    .iter()
+                    .zip(&policy.weights)
+                    .filter_map(|(l, w)| l.as_deref().and_then(letter_points).map(|p| (*w, p)))
+                    .collect(),
+            };
+
+            let projected = if policy.exam_weight > 0.0 {
+                exam.and_then(|e| policy.combine(&components, Some(e)))
+            } else {
+                policy.combine(&components, None)
+            };
+
+            let needed_exam = if policy.exam_weight > 0.0 && !components.is_empty() {
+                LETTER_SCALE
+                    .iter()
+                    .map(|(letter, _)| ExamTarget {
+                        letter: letter.to_string(),
+                        exam: policy.needed_exam(&components, letter),
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            ClassProjection {
+                id: id.to_string(),
+                name,
+                grades,
+                letters,
+                projected_grade: projected.filter(|_| policy.method == Method::Percent),
+                projected_letter: projected.map(|p| policy.letter(p).to_string()),
+                needed_exam,
+            }
+        })
+        .collect();
+
+    Projection {
+        periods: books.iter().map(|b| b.report_period).collect(),
+        method: policy.method,
+        classes,
+    }
+}
+
+fn letter_points(letter: &str) -> Option<f32> {
+    let first = letter.trim().chars().next()?.to_ascii_uppercase();
+    LETTER_SCALE
+        .iter()
+        .position(|(l, _)| l.starts_with(first))
+        .map(|idx| (LETTER_SCALE.len() - 1 - idx) as f32)
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Method {
+    /// Average the period percentages and the exam by weight
+    Percent,
+    /// Average letter points (A = 4 ... E = 0) by weight, rounding half up
+    #[default]
+    Points,
+}
+
+impl std::str::FromStr for Method {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "percent" => Ok(Method::Percent),
+            "points" => Ok(Method::Points),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How a district combines marking periods (and optionally an exam) into one grade.
+#[derive(Clone, Debug)]
+pub struct Policy {
+    pub method: Method,
+    // fractions of the final grade, together with `exam_weight` they add up to 1
+    pub weights: Vec<f32>,
+    pub exam_weight: f32,
+}
+
+impl Policy {
+    fn combine(&self, components: &[(f32, f32)], exam: Option<f32>) -> Option<f32> {
+        let exam = match (self.method, exam) {
+            (Method::Points, Some(e)) => letter_points(letter_grade(e)),
+            (_, e) => e,
+        };
+
+        let (mut total, mut weight) = components
+            .iter()
+            .fold((0.0, 0.0), |acc, (w, v)| (acc.0 + w * v, acc.1 + w));
+        if let Some(exam) = exam {
+            total += self.exam_weight * exam;
+            weight += self.exam_weight;
+        }
+
+        (weight > 0.0).then(|| total / weight)
+    }
+
+    fn letter(&self, value: f32) -> &'static str {
+        match self.method {
+            Method::Percent => letter_grade(value),
+            Method::Points => {
+                let points = (value + 0.5).floor().clamp(0.0, 4.0) as usize;
+                LETTER_SCALE[LETTER_SCALE.len() - 1 - points].0
+            }
+        }
+    }
+
+    /// The lowest exam percentage that still earns `letter`, `None` if even 100% won't.
+    fn needed_exam(&self, components: &[(f32, f32)], letter: &str) -> Option<f32> {
+        let rank = |l: &str| LETTER_SCALE.iter().position(|(x, _)| *x == l);
+        let target = rank(letter)?;
+
+        // exam grades only matter at the letter cutoffs under points, so walk those upward
+        let candidates: Vec<f32> = match self.method {
+            Method::Points => LETTER_SCALE.iter().rev().map(|(_, min)| *min).collect(),
+            Method::Percent => {
+                let (known, weight) = components
+                    .iter()
+                    .fold((0.0, 0.0), |acc, (w, v)| (acc.0 + w * v, acc.1 + w));
+                let min = LETTER_SCALE[target].1;
+                let exam = (min * (weight + self.exam_weight) - known) / self.exam_weight;
+                vec![((exam * 100.0).ceil() / 100.0).max(0.0)]
+            }
+        };
+
+        candidates.into_iter().filter(|e| *e <= 100.0).find(|e| {
+            self.combine(components, Some(*e))
+                .and_then(|p| rank(self.letter(p)))
+                .is_some_and(|r| r <= target)
+        })
+    }
+}
+
+// API structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MissingReport {
+    report_period: i32,
+    classes: Vec<MissingClass>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct MissingClass {
+    name: String,
+    teacher: String,
+    grade: f32,
+    letter_grade: String,
+    assignments: Vec<MissingAssignment>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct MissingAssignment {
+    name: String,
+    kind: String,
+    due_date: String,
+    status: ScoreStatus,
+    points_possible: f32,
+    // percentage points between getting full credit and a zero
+    impact: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClassDetail {
+    report_period: i32,
+    #[serde(flatten)]
+    class: Class,
+    analytics: ClassAnalytics,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ClassAnalytics {
+    computed_grade: Option<f32>,
+    category_grades: HashMap<String, f32>,
+    outstanding: usize,
+    most_helpful: Option<String>,
+    most_harmful: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Comparison {
+    from: i32,
+    to: i32,
+    classes: Vec<ClassComparison>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ClassComparison {
+    id: String,
+    name: String,
+    from_grade: Option<f32>,
+    to_grade: Option<f32>,
+    delta: Option<f32>,
+    from_letter: Option<String>,
+    to_letter: Option<String>,
+    letter_changed: bool,
+    categories: HashMap<String, CategoryShift>,
+}
+
+// changes from the earlier period to the later one
+#[derive(Serialize, Deserialize, Debug)]
+struct CategoryShift {
+    weight: f32,
+    points_earned: f32,
+    points_possible: f32,
+    percent: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Projection {
+    periods: Vec<i32>,
+    method: Method,
+    classes: Vec<ClassProjection>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ClassProjection {
+    id: String,
+    name: String,
+    grades: Vec<Option<f32>>,
+    letters: Vec<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    projected_grade: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    projected_letter: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    needed_exam: Vec<ExamTarget>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ExamTarget {
+    letter: String,
+    exam: Option<f32>,
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{api_request, parse_date, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+/// Standardized test results, newest first.
+pub async fn test_history(token: &mut AuthToken) -> Result<Vec<Test>, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default(
+            "GetStudentTestHistory".to_string(),
+            String::new(),
+            token,
+        ),
+        token,
+    )
+    .await?;
+
+    let data: StudentTestHistoryData = quick_xml::de::from_str(result.as_str())?;
+    let mut tests: Vec<Test> = data
+        .test_histories
+        .test_history
+        .into_iter()
+        .map(|x| x.into())
+        .collect();
+
+    tests.sort_by_key(|t| std::cmp::Reverse(parse_date(&t.date)));
+    Ok(tests)
+}
+
+fn number(value: &str) -> Option<f32> {
+    value.trim().trim_end_matches('%').parse().ok()
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Test {
+    name: String,
+    date: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    grade_level: String,
+    scores: Vec<Score>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Score {
+    // the part of the test, e.g. "Math" or "Reading"
+    subject: String,
+    // as reported, e.g. the number correct, scores aren't always numbers
+    score: String,
+    scale_score: Option<f32>,
+    percentile: Option<f32>,
+    // e.g. "Meets Standard"
+    #[serde(skip_serializing_if = "String::is_empty")]
+    performance_level: String,
+}
+
+impl From<TestHistory> for Test {
+    fn from(value: TestHistory) -> Self {
+        Test {
+            name: value.test_name,
+            date: value.test_date,
+            grade_level: value.grade,
+            scores: value
+                .test_scores
+                .test_score
+                .into_iter()
+                .map(|x| x.into())
+                .collect(),
+        }
+    }
+}
+
+impl From<TestScore> for Score {
+    fn from(value: TestScore) -> Self {
+        Score {
+            subject: value.subject,
+            scale_score: number(&value.scale_score),
+            percentile: number(&value.percentile),
+            score: value.score,
+            performance_level: value.performance_level,
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct StudentTestHistoryData {
+    #[serde(rename = "TestHistories")]
+    #[serde(default)]
+    pub test_histories: TestHistories,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct TestHistories {
+    #[serde(rename = "TestHistory")]
+    #[serde(default)]
+    pub test_history: Vec<TestHistory>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TestHistory {
+    #[serde(rename = "@TestName")]
+    #[serde(default)]
+    pub test_name: String,
+    #[serde(rename = "@TestDate")]
+    #[serde(default)]
+    pub test_date: String,
+    #[serde(rename = "@Grade")]
+    #[serde(default)]
+    pub grade: String,
+    #[serde(rename = "TestScores")]
+    #[serde(default)]
+    pub test_scores: TestScores,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct TestScores {
+    #[serde(rename = "TestScore")]
+    #[serde(default)]
+    pub test_score: Vec<TestScore>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TestScore {
+    #[serde(rename = "@Subject")]
+    #[serde(default)]
+    pub subject: String,
+    #[serde(rename = "@Score")]
+    #[serde(default)]
+    pub score: String,
+    #[serde(rename = "@ScaleScore")]
+    #[serde(default)]
+    pub scale_score: String,
+    #[serde(rename = "@Percentile")]
+    #[serde(default)]
+    pub percentile: String,
+    #[serde(rename = "@PerformanceLevel")]
+    #[serde(default)]
+    pub performance_level: String,
+}
@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+use crate::api::gradebook::{Assignment, Response, ScoreStatus};
+use crate::api::parse_date;
+
+pub fn search(books: &[Response], filter: &Filter) -> Vec<AssignmentEntry> {
+    let contains = |haystack: &str, needle: &str| {
+        haystack.to_lowercase().contains(&needle.to_lowercase())
+    };
+
+    let mut entries: Vec<AssignmentEntry> = books
+        .iter()
+        .flat_map(|gb| gb.classes.iter().map(move |c| (gb.report_period, c)))
+        .filter(|(_, c)| {
+            filter
+                .class
+                .as_deref()
+                .is_none_or(|class| c.id == class || contains(&c.name, class))
+        })
+        .flat_map(|(rp, c)| {
+            c.assignments.iter().map(move |a| AssignmentEntry {
+                report_period: rp,
+                class_id: c.id.clone(),
+                class_name: c.name.clone(),
+                assignment: a.clone(),
+            })
+        })
+        .filter(|e| {
+            let a = &e.assignment;
+            let date = parse_date(&a.date);
+
+            filter.kind.as_deref().is_none_or(|k| a.kind.eq_ignore_ascii_case(k))
+                && filter.status.is_none_or(|s| a.status == s)
+                && filter.from.is_none_or(|from| date.is_some_and(|d| d >= from))
+                && filter.to.is_none_or(|to| date.is_some_and(|d| d <= to))
+                && filter
+                    .text
+                    .as_deref()
+                    .is_none_or(|q| contains(&a.name, q) || contains(&a.notes, q))
+        })
+        .collect();
+
+    // entries without a date or score always go last, whichever way the sort runs
+    let direction = |ord: Ordering| if filter.descending { ord.reverse() } else { ord };
+    match filter.sort {
+        Some(Sort::Date) => entries.sort_by(|a, b| {
+            let (a, b) = (parse_date(&a.assignment.date), parse_date(&b.assignment.date));
+            missing_last(a, b, |a, b| direction(a.cmp(&b)))
+        }),
+        Some(Sort::Score) => entries.sort_by(|a, b| {
+            missing_last(a.percent(), b.percent(), |a, b| direction(a.total_cmp(&b)))
+        }),
+        None if filter.descending => entries.reverse(),
+        None => {}
+    }
+
+    entries
+}
+
+fn missing_last<T>(a: Option<T>, b: Option<T>, cmp: impl FnOnce(T, T) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => cmp(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Sort {
+    Date,
+    Score,
+}
+
+#[derive(Default, Debug)]
+pub struct Filter {
+    /// A class id, or part of its name
+    pub class: Option<String>,
+    pub kind: Option<String>,
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+    pub status: Option<ScoreStatus>,
+    /// Matched against the assignment name and notes
+    pub text: Option<String>,
+    pub sort: Option<Sort>,
+    pub descending: bool,
+}
+
+// API structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssignmentEntry {
+    report_period: i32,
+    class_id: String,
+    class_name: String,
+    #[serde(flatten)]
+    assignment: Assignment,
+}
+
+impl AssignmentEntry {
+    fn percent(&self) -> Option<f32> {
+        let a = &self.assignment;
+        (a.points_earned.is_finite() && a.points_possible > 0.0)
+            .then(|| a.points_earned / a.points_possible)
+    }
+}
@@ -0,0 +1,185 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+
+use crate::api::{api_request, parse_date, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+/// The events between `from` and `to` (inclusive). StudentVue answers one month at a time, so
+/// a few months are requested at once.
+pub async fn calendar(token: &mut AuthToken, from: Date, to: Date) -> Result<Calendar, ApiError> {
+    let mut months = Vec::new();
+    let mut month = from.replace_day(1).map_err(|_| ApiError::InvalidQuery)?;
+    while month <= to {
+        months.push(month);
+        month = next_month(month).ok_or(ApiError::InvalidQuery)?;
+    }
+
+    let listings: Vec<_> = stream::iter(months)
+        .map(|month| {
+            let mut token = token.clone();
+            async move {
+                let listing = get_month(&mut token, month).await?;
+                Ok::<_, ApiError>((token, listing))
+            }
+        })
+        .buffered(4)
+        .try_collect()
+        .await?;
+
+    // keep whichever session cookie StudentVue handed out
+    if let Some((t, _)) = listings.first() {
+        token.cookie = t.cookie.clone();
+    }
+
+    let mut calendar = Calendar::default();
+    for (_, listing) in listings {
+        calendar.school_start = listing.school_beg_date;
+        calendar.school_end = listing.school_end_date;
+
+        for event in listing.event_lists.event_list {
+            let event: Event = event.into();
+            let in_range = parse_date(&event.date).is_some_and(|d| from <= d && d <= to);
+            if in_range && !calendar.events.contains(&event) {
+                calendar.events.push(event);
+            }
+        }
+    }
+
+    calendar.events.sort_by_key(|e| parse_date(&e.date));
+    Ok(calendar)
+}
+
+async fn get_month(token: &mut AuthToken, month: Date) -> Result<CalendarListing, ApiError> {
+    let params = format!(
+        "<RequestDate>{}/{}/{}</RequestDate>",
+        month.month() as u8,
+        month.day(),
+        month.year()
+    );
+
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default("StudentCalendar".to_string(), params, token),
+        token,
+    )
+    .await?;
+
+    Ok(quick_xml::de::from_str(result.as_str())?)
+}
+
+fn next_month(month: Date) -> Option<Date> {
+    let next = month.month().next();
+    let year = if next == Month::January {
+        month.year() + 1
+    } else {
+        month.year()
+    };
+    Date::from_calendar_date(year, next, 1).ok()
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Calendar {
+    school_start: String,
+    school_end: String,
+    events: Vec<Event>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Event {
+    date: String,
+    title: String,
+    kind: EventKind,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    start_time: String,
+    // the gradebook assignment this event is for, if any
+    #[serde(skip_serializing_if = "String::is_empty")]
+    assignment_gu: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+enum EventKind {
+    /// No school
+    Holiday,
+    /// An assignment due date
+    Assignment,
+    /// A school day, usually with a teacher or school posted event
+    Regular,
+    Other,
+}
+
+impl From<EventList> for Event {
+    fn from(value: EventList) -> Self {
+        let kind = match value.day_type.trim().to_lowercase().as_str() {
+            "holiday" => EventKind::Holiday,
+            "assignment" => EventKind::Assignment,
+            "regular" => EventKind::Regular,
+            _ => EventKind::Other,
+        };
+
+        Event {
+            date: value.date,
+            title: value.title,
+            kind,
+            start_time: value.start_time,
+            assignment_gu: value.agu,
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct CalendarListing {
+    #[serde(rename = "@SchoolBegDate")]
+    #[serde(default)]
+    pub school_beg_date: String,
+    #[serde(rename = "@SchoolEndDate")]
+    #[serde(default)]
+    pub school_end_date: String,
+    #[serde(rename = "@MonthBegDate")]
+    #[serde(default)]
+    pub month_beg_date: String,
+    #[serde(rename = "@MonthEndDate")]
+    #[serde(default)]
+    pub month_end_date: String,
+    #[serde(rename = "EventLists")]
+    #[serde(default)]
+    pub event_lists: EventLists,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct EventLists {
+    #[serde(rename = "EventList")]
+    #[serde(default)]
+    pub event_list: Vec<EventList>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventList {
+    #[serde(rename = "@Date")]
+    #[serde(default)]
+    pub date: String,
+    #[serde(rename = "@Title")]
+    #[serde(default)]
+    pub title: String,
+    #[serde(rename = "@DayType")]
+    #[serde(default)]
+    pub day_type: String,
+    #[serde(rename = "@StartTime")]
+    #[serde(default)]
+    pub start_time: String,
+    #[serde(rename = "@Icon")]
+    #[serde(default)]
+    pub icon: String,
+    #[serde(rename = "@AGU")]
+    #[serde(default)]
+    pub agu: String,
+    #[serde(rename = "@DGU")]
+    #[serde(default)]
+    pub dgu: String,
+    #[serde(rename = "@Link")]
+    #[serde(default)]
+    pub link: String,
+}
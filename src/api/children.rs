@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{api_request, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+/// The students linked to a parent account. Their `id` is what `x-child-id` and
+/// `AuthToken::child` select.
+pub async fn list_children(token: &mut AuthToken) -> Result<Vec<Child>, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default("ChildList".to_string(), String::new(), token),
+        token,
+    )
+    .await?;
+
+    let data: ChildList = quick_xml::de::from_str(result.as_str())?;
+    Ok(data
+        .child
+        .into_iter()
+        .enumerate()
+        .map(|(idx, child)| Child {
+            id: idx as u32,
+            gu: child.student_gu,
+            name: child.child_name,
+            school: child.organization_name,
+            grade: child.grade,
+        })
+        .collect())
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Child {
+    pub id: u32,
+    gu: String,
+    name: String,
+    school: String,
+    grade: String,
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct ChildList {
+    #[serde(rename = "Child")]
+    #[serde(default)]
+    pub child: Vec<ChildXML>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChildXML {
+    #[serde(rename = "@StudentGU")]
+    #[serde(default)]
+    pub student_gu: String,
+    #[serde(rename = "ChildName")]
+    #[serde(default)]
+    pub child_name: String,
+    #[serde(rename = "OrganizationName")]
+    #[serde(default)]
+    pub organization_name: String,
+    #[serde(rename = "Grade")]
+    #[serde(default)]
+    pub grade: String,
+}
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{api_request, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+pub async fn course_history(token: &mut AuthToken) -> Result<CourseHistory, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default(
+            "StudentGradeHistory".to_string(),
+            String::new(),
+            token,
+        ),
+        token,
+    )
+    .await?;
+
+    let history: CourseHistoryXML = quick_xml::de::from_str(result.as_str())?;
+    Ok(history.into())
+}
+
+/// Grade points on a 4.0 scale, with `+`/`-` worth 0.3 either way (A+ stays at 4.0), or
+/// `None` for marks that don't count towards a GPA (pass/fail, incompletes, ...).
+fn grade_points(mark: &str) -> Option<f32> {
+    let mut chars = mark.trim().chars();
+    let base = match chars.next()?.to_ascii_uppercase() {
+        'A' => 4.0,
+        'B' => 3.0,
+        'C' => 2.0,
+        'D' => 1.0,
+        'E' | 'F' => return chars.as_str().is_empty().then_some(0.0),
+        _ => return None,
+    };
+
+    let points: f32 = match chars.as_str() {
+        "" => base,
+        "+" => base + 0.3,
+        "-" => base - 0.3,
+        _ => return None,
+    };
+    Some(points.min(4.0))
+}
+
+/// Credits and GPA over a set of courses.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug)]
+struct Totals {
+    credits_attempted: f32,
+    credits_earned: f32,
+    /// Unweighted, weighted by credits attempted. `None` until there's a graded course.
+    gpa: Option<f32>,
+    #[serde(skip)]
+    points: f32,
+    #[serde(skip)]
+    graded_credits: f32,
+}
+
+impl Totals {
+    fn add(mut self, course: &Course) -> Self {
+        self.credits_attempted += course.credits_attempted;
+        self.credits_earned += course.credits_earned;
+
+        if let Some(points) = grade_points(&course.mark).filter(|_| course.credits_attempted > 0.0)
+        {
+            self.points += points * course.credits_attempted;
+            self.graded_credits += course.credits_attempted;
+            self.gpa = Some(self.points / self.graded_credits);
+        }
+
+        self
+    }
+}
+
+fn credits(value: &str) -> f32 {
+    value.trim().parse().unwrap_or_default()
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CourseHistory {
+    terms: Vec<Term>,
+    #[serde(flatten)]
+    totals: Totals,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Term {
+    school_year: String,
+    name: String,
+    school: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    grade_level: String,
+    courses: Vec<Course>,
+    /// This term alone
+    term: Totals,
+    /// Every term up to and including this one
+    cumulative: Totals,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Course {
+    id: String,
+    title: String,
+    mark: String,
+    credits_attempted: f32,
+    credits_earned: f32,
+    // e.g. Honors or AP, where the district tracks it
+    #[serde(skip_serializing_if = "String::is_empty")]
+    level: String,
+}
+
+impl From<CourseHistoryXML> for CourseHistory {
+    fn from(value: CourseHistoryXML) -> Self {
+        let mut terms = Vec::new();
+        for school in value.ch_school {
+            for term in school.ch_terms.ch_term {
+                terms.push(Term {
+                    school_year: term.school_year,
+                    name: term.term_name,
+                    school: school.school_name.clone(),
+                    grade_level: term.grade,
+                    courses: term
+                        .ch_courses
+                        .ch_course
+                        .into_iter()
+                        .map(|c| c.into())
+                        .collect(),
+                    term: Totals::default(),
+                    cumulative: Totals::default(),
+                });
+            }
+        }
+        terms.sort_by(|a, b| a.school_year.cmp(&b.school_year));
+
+        let mut totals = Totals::default();
+        for term in &mut terms {
+            term.term = term.courses.iter().fold(Totals::default(), Totals::add);
+            totals = term.courses.iter().fold(totals, Totals::add);
+            term.cumulative = totals;
+        }
+
+        CourseHistory { terms, totals }
+    }
+}
+
+impl From<CHCourse> for Course {
+    fn from(value: CHCourse) -> Self {
+        Course {
+            id: value.course_id,
+            title: value.course_title,
+            mark: value.mark,
+            credits_attempted: credits(&value.credit_attempted),
+            credits_earned: credits(&value.credit_completed),
+            level: value.chs_type,
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct CourseHistoryXML {
+    #[serde(rename = "CHSchool")]
+    #[serde(default)]
+    pub ch_school: Vec<CHSchool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CHSchool {
+    #[serde(rename = "@SchoolName")]
+    #[serde(default)]
+    pub school_name: String,
+    #[serde(rename = "CHTerms")]
+    #[serde(default)]
+    pub ch_terms: CHTerms,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CHTerms {
+    #[serde(rename = "CHTerm")]
+    #[serde(default)]
+    pub ch_term: Vec<CHTerm>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CHTerm {
+    #[serde(rename = "@SchoolYear")]
+    #[serde(default)]
+    pub school_year: String,
+    #[serde(rename = "@TermName")]
+    #[serde(default)]
+    pub term_name: String,
+    #[serde(rename = "@Grade")]
+    #[serde(default)]
+    pub grade: String,
+    #[serde(rename = "CHCourses")]
+    #[serde(default)]
+    pub ch_courses: CHCourses,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CHCourses {
+    #[serde(rename = "CHCourse")]
+    #[serde(default)]
+    pub ch_course: Vec<CHCourse>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CHCourse {
+    #[serde(rename = "@CourseID")]
+    #[serde(default)]
+    pub course_id: String,
+    #[serde(rename = "@CourseTitle")]
+    #[serde(default)]
+    pub course_title: String,
+    #[serde(rename = "@Mark")]
+    #[serde(default)]
+    pub mark: String,
+    #[serde(rename = "@CreditAttempted")]
+    #[serde(default)]
+    pub credit_attempted: String,
+    #[serde(rename = "@CreditCompleted")]
+    #[serde(default)]
+    pub credit_completed: String,
+    #[serde(rename = "@CHSType")]
+    #[serde(default)]
+    pub chs_type: String,
+}
@@ -1,10 +1,20 @@
 use crate::{
-    api::{api_request, ApiError, ProcessWebServiceRequest},
+    api::{api_request, escape_attr, parse_date, ApiError, ProcessWebServiceRequest},
+    api::memo::Downloads,
     crypto::AuthToken,
 };
+use std::collections::HashSet;
+use std::pin::pin;
+
+use async_zip::base::write::ZipFileWriter;
+use axum::body::Bytes;
+use async_zip::{Compression, ZipEntryBuilder};
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+use futures::{stream, StreamExt};
 use serde::{Deserialize, Deserializer, Serialize};
+use time::Date;
+use tokio::io::DuplexStream;
 
 pub async fn list_documents(token: &mut AuthToken) -> Result<Vec<Document>, ApiError> {
     let result = api_request(
@@ -30,7 +40,7 @@ pub async fn get_document(token: &mut AuthToken, gu: String) -> Result<DocumentD
     let result = api_request(
         ProcessWebServiceRequest::ck_default(
             "GetContentOfAttachedDoc".to_string(),
-            format!("<DocumentGU>{gu}</DocumentGU>"),
+            format!("<DocumentGU>{}</DocumentGU>", escape_attr(&gu)),
             token,
         ),
         token,
@@ -41,13 +51,133 @@ pub async fn get_document(token: &mut AuthToken, gu: String) -> Result<DocumentD
     Ok(docs.document_datas.document_data.into())
 }
 
+lazy_static::lazy_static! {
+    static ref DOCUMENTS: Downloads<DocumentData> = Downloads::new();
+}
+
+/// Like `get_document`, but keeps the file around for a few minutes.
+pub async fn cached_document(token: &mut AuthToken, gu: String) -> Result<DocumentData, ApiError> {
+    DOCUMENTS.get_or_fetch(token, gu, get_document).await
+}
+
+/// Streams every document into a ZIP archive, fetching a few at a time. Nothing is sent until
+/// one document has downloaded, so an expired session is still a proper error. Documents that
+/// fail after that are listed in `errors.txt` rather than breaking an archive that's already
+/// being sent.
+pub async fn archive(token: &AuthToken, docs: Vec<Document>) -> Result<DuplexStream, ApiError> {
+    let token = token.clone();
+    let mut fetches = stream::iter(docs)
+        .map(move |doc| {
+            let mut token = token.clone();
+            async move {
+                let result = get_document(&mut token, doc.gu).await;
+                (doc.file_name, result)
+            }
+        })
+        .buffered(4);
+
+    let mut failed = Vec::new();
+    let first = loop {
+        match fetches.next().await {
+            Some((_, Ok(doc))) => break Some(doc),
+            Some((name, Err(e))) => failed.push((name, e)),
+            None => break None,
+        }
+    };
+
+    // every single download failed, most likely for the same reason
+    if first.is_none() {
+        if let Some((_, e)) = failed.pop() {
+            return Err(e);
+        }
+    }
+
+    let (reader, writer) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        let mut zip = ZipFileWriter::with_tokio(writer);
+        let mut used = HashSet::new();
+
+        {
+            let mut docs = pin!(stream::iter(first).chain(fetches.filter_map(|(name, result)| {
+                let doc = match result {
+                    Ok(doc) => Some(doc),
+                    Err(e) => {
+                        failed.push((name, e));
+                        None
+                    }
+                };
+                async move { doc }
+            })));
+
+            while let Some(doc) = docs.next().await {
+                let name = unique_file_name(&doc.file_name, &mut used);
+                let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+                if zip.write_entry_whole(entry, &doc.file_data).await.is_err() {
+                    // the client went away
+                    return;
+                }
+            }
+        }
+
+        if !failed.is_empty() {
+            let manifest: String = failed
+                .iter()
+                .map(|(name, e)| format!("{name}: {e}\n"))
+                .collect();
+            let name = unique_file_name("errors.txt", &mut used);
+            let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+            let _ = zip.write_entry_whole(entry, manifest.as_bytes()).await;
+        }
+
+        let _ = zip.close().await;
+    });
+
+    Ok(reader)
+}
+
+/// Makes a file name safe to extract on any OS and distinct from the ones already `used`,
+/// turning a second `report.pdf` into `report (2).pdf`.
+fn unique_file_name(name: &str, used: &mut HashSet<String>) -> String {
+    let clean: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let clean = clean.trim().trim_start_matches('.');
+    let clean = if clean.is_empty() { "document" } else { clean };
+
+    let (stem, ext) = match clean.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (clean, String::new()),
+    };
+
+    let mut candidate = clean.to_string();
+    let mut n = 2;
+    while !used.insert(candidate.to_lowercase()) {
+        candidate = format!("{stem} ({n}){ext}");
+        n += 1;
+    }
+
+    candidate
+}
+
+// `YYYY-MM-DD` when StudentVue's date parses, otherwise left as sent
+fn iso_date(date: String) -> String {
+    parse_date(&date).map_or(date, |d| d.to_string())
+}
+
 // Api structs
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Document {
     pub name: String,
-    file_name: String,
+    pub file_name: String,
     date: String,
     pub gu: String,
+    kind: String,
+    category: String,
 }
 
 impl From<StudentDocumentData> for Document {
@@ -55,23 +185,67 @@ impl From<StudentDocumentData> for Document {
         Document {
             name: value.document_comment,
             file_name: value.document_file_name,
-            date: value.document_date,
+            date: iso_date(value.document_date),
             gu: value.document_gu,
+            // most districts only fill in the type, which is what the category column shows
+            category: value
+                .document_category
+                .filter(|c| !c.trim().is_empty())
+                .unwrap_or_else(|| value.document_type.clone()),
+            kind: value.document_type,
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Debug)]
+pub struct DocumentFilter {
+    pub kind: Option<String>,
+    pub category: Option<String>,
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+}
+
+impl DocumentFilter {
+    pub fn matches(&self, doc: &Document) -> bool {
+        let date = parse_date(&doc.date);
+
+        self.kind
+            .as_deref()
+            .is_none_or(|k| doc.kind.trim().eq_ignore_ascii_case(k.trim()))
+            && self
+                .category
+                .as_deref()
+                .is_none_or(|c| doc.category.trim().eq_ignore_ascii_case(c.trim()))
+            && self.from.is_none_or(|from| date.is_some_and(|d| d >= from))
+            && self.to.is_none_or(|to| date.is_some_and(|d| d <= to))
+    }
+}
+
+/// A downloaded document. Only the metadata is serialized, the contents are sent as is.
+#[derive(Serialize, Deserialize, Clone)]
 pub struct DocumentData {
+    pub gu: String,
     pub file_name: String,
-    pub file_data: Vec<u8>,
+    pub date: String,
+    pub category: String,
+    pub doc_type: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    // shared, so cache hits and range requests don't copy the file
+    #[serde(skip)]
+    pub file_data: Bytes,
 }
 
 impl From<DocumentData_> for DocumentData {
     fn from(value: DocumentData_) -> Self {
         DocumentData {
+            gu: value.document_gu,
             file_name: value.file_name,
-            file_data: value.data,
+            date: iso_date(value.doc_date),
+            category: value.category,
+            doc_type: value.doc_type,
+            notes: value.notes,
+            file_data: value.data.into(),
         }
     }
 }
@@ -114,6 +288,8 @@ pub struct StudentDocumentData {
     pub document_date: String,
     #[serde(rename = "@DocumentType")]
     pub document_type: String,
+    #[serde(rename = "@DocumentCategory")]
+    pub document_category: Option<String>,
     #[serde(rename = "@StudentGU")]
     pub student_gu: String,
     #[serde(rename = "@DocumentComment")]
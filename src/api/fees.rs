@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{api_request, parse_date, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+pub async fn fees(token: &mut AuthToken) -> Result<Fees, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default("StudentFees".to_string(), String::new(), token),
+        token,
+    )
+    .await?;
+
+    let data: StudentFeesData = quick_xml::de::from_str(result.as_str())?;
+    Ok(data.into())
+}
+
+// amounts come formatted, e.g. "$1,250.00" or "(5.00)" for credits. `None` when the amount
+// can't be read, a wrong number is worse than none.
+fn parse_amount(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, value),
+    };
+
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+    let amount: f64 = cleaned.parse().ok()?;
+    Some(if negative { -amount } else { amount })
+}
+
+// a sum is only known if every part of it is
+fn total(amounts: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    amounts.sum()
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Fees {
+    // the totals are None when any fee's amount couldn't be read
+    total: Option<f64>,
+    paid: Option<f64>,
+    balance: Option<f64>,
+    /// Fees with a balance left (or an unreadable one), soonest due first
+    outstanding: Vec<Fee>,
+    /// Fees that are paid off or waived
+    settled: Vec<Fee>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Fee {
+    description: String,
+    // e.g. "Lunch", "Athletics" or a course name
+    category: String,
+    date: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    due_date: String,
+    // None when StudentVue's amount is missing or unreadable, `raw_*` then has it as sent
+    amount: Option<f64>,
+    paid: Option<f64>,
+    balance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_paid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_balance: Option<String>,
+}
+
+impl From<StudentFeesData> for Fees {
+    fn from(value: StudentFeesData) -> Self {
+        let (mut outstanding, settled): (Vec<Fee>, Vec<Fee>) = value
+            .fee_listings
+            .fee_listing
+            .into_iter()
+            .map(Fee::from)
+            .partition(|fee| fee.balance.is_none_or(|b| b > 0.0));
+
+        // fees without a due date go last
+        outstanding.sort_by_key(|fee| {
+            let due = parse_date(&fee.due_date);
+            (due.is_none(), due)
+        });
+
+        let all = || outstanding.iter().chain(&settled);
+        Fees {
+            total: total(all().map(|f| f.amount)),
+            paid: total(all().map(|f| f.paid)),
+            balance: total(all().map(|f| f.balance)),
+            outstanding,
+            settled,
+        }
+    }
+}
+
+impl From<FeeListing> for Fee {
+    fn from(value: FeeListing) -> Self {
+        let amount = parse_amount(&value.amount);
+        // nothing paid yet is often left blank
+        let paid = if value.amount_paid.trim().is_empty() {
+            Some(0.0)
+        } else {
+            parse_amount(&value.amount_paid)
+        };
+        // and so is the balance
+        let balance = if value.balance.trim().is_empty() {
+            amount.zip(paid).map(|(amount, paid)| amount - paid)
+        } else {
+            parse_amount(&value.balance)
+        };
+        let raw = |parsed: Option<f64>, raw: &str| {
+            (parsed.is_none() && !raw.trim().is_empty()).then(|| raw.to_string())
+        };
+
+        Fee {
+            description: value.description,
+            category: value.category,
+            date: value.date,
+            due_date: value.due_date,
+            raw_amount: raw(amount, &value.amount),
+            raw_paid: raw(paid, &value.amount_paid),
+            raw_balance: raw(balance, &value.balance),
+            amount,
+            paid,
+            balance,
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct StudentFeesData {
+    #[serde(rename = "FeeListings")]
+    #[serde(default)]
+    pub fee_listings: FeeListings,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct FeeListings {
+    #[serde(rename = "FeeListing")]
+    #[serde(default)]
+    pub fee_listing: Vec<FeeListing>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeeListing {
+    #[serde(rename = "@Description")]
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "@Category")]
+    #[serde(default)]
+    pub category: String,
+    #[serde(rename = "@Date")]
+    #[serde(default)]
+    pub date: String,
+    #[serde(rename = "@DueDate")]
+    #[serde(default)]
+    pub due_date: String,
+    #[serde(rename = "@Amount")]
+    #[serde(default)]
+    pub amount: String,
+    #[serde(rename = "@AmountPaid")]
+    #[serde(default)]
+    pub amount_paid: String,
+    #[serde(rename = "@Balance")]
+    #[serde(default)]
+    pub balance: String,
+}
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 use std::num::ParseFloatError;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use time::{Date, OffsetDateTime};
 
-use crate::api::{api_request, ApiError, ProcessWebServiceRequest};
+use crate::api::memo::Memo;
+use crate::api::{api_request, parse_date, ApiError, ProcessWebServiceRequest};
 use crate::crypto::AuthToken;
 
 #[derive(Error, Debug)]
@@ -15,6 +18,25 @@ pub enum GradebookError {
     NumParsing(#[from] ParseFloatError),
     #[error("Bad point_string")]
     InvalidPointString,
+    #[error("No reporting period matches '{0}'")]
+    UnknownPeriod(String),
+}
+
+lazy_static::lazy_static! {
+    // class detail screens and analytics reuse the last gradebook instead of refetching it
+    static ref GRADEBOOKS: Memo<(String, Option<i32>), Response> =
+        Memo::new(Duration::from_secs(60));
+}
+
+/// Like `get_grade_book`, but may be up to a minute old. Only for class details and analytics,
+/// which tend to be opened right after the gradebook itself.
+pub async fn cached_grade_book(
+    token: &mut AuthToken,
+    rp: Option<i32>,
+) -> Result<Response, ApiError> {
+    GRADEBOOKS
+        .get_or_try_init((token.identity(), rp), get_grade_book(token, rp))
+        .await
 }
 
 pub async fn get_grade_book(token: &mut AuthToken, rp: Option<i32>) -> Result<Response, ApiError> {
@@ -32,10 +54,31 @@ pub async fn get_grade_book(token: &mut AuthToken, rp: Option<i32>) -> Result<Re
     Ok(gb.try_into()?)
 }
 
+/// Resolves a reporting period given as its index, its name, a date inside it or `current`.
+/// `None` means the current period, which StudentVue picks when no index is sent.
+pub async fn resolve_period(
+    token: &mut AuthToken,
+    period: Option<&str>,
+) -> Result<Option<i32>, ApiError> {
+    let Some(period) = period.map(str::trim) else {
+        return Ok(None);
+    };
+
+    if period.eq_ignore_ascii_case("current") {
+        return Ok(None);
+    }
+    if let Ok(idx) = period.parse() {
+        return Ok(Some(idx));
+    }
+
+    let gb = cached_grade_book(token, None).await?;
+    Ok(Some(gb.find_period(period)?))
+}
+
 // API structs
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
 pub struct Response {
-    classes: Vec<Class>,
+    pub(crate) classes: Vec<Class>,
     pub report_period: i32,
     pub reporting_periods: Vec<ReportingPeriod>,
 }
@@ -47,32 +90,222 @@ pub struct ReportingPeriod {
     end_date: String,
 }
 
+impl ReportingPeriod {
+    pub fn start(&self) -> Option<Date> {
+        parse_date(&self.start_date)
+    }
+
+    pub fn end(&self) -> Option<Date> {
+        parse_date(&self.end_date)
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        self.start().is_some_and(|s| s <= date) && self.end().is_some_and(|e| date <= e)
+    }
+}
+
+impl Response {
+    /// Finds a period index by index, name (case insensitive), date or `current`.
+    pub fn find_period(&self, period: &str) -> Result<i32, GradebookError> {
+        let period = period.trim();
+        if period.eq_ignore_ascii_case("current") {
+            return Ok(self.report_period);
+        }
+        if let Ok(idx) = period.parse() {
+            return Ok(idx);
+        }
+
+        let date = parse_date(period);
+
+        self.reporting_periods
+            .iter()
+            .position(|rp| match date {
+                Some(date) => rp.contains(date),
+                None => rp.name.trim().eq_ignore_ascii_case(period),
+            })
+            .map(|idx| idx as i32)
+            .ok_or_else(|| GradebookError::UnknownPeriod(period.to_string()))
+    }
+}
+
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
-struct Class {
-    name: String,
-    teacher: String,
+pub(crate) struct Class {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) period: String,
+    pub(crate) room: String,
+    pub(crate) teacher: String,
+    pub(crate) teacher_email: String,
     category: String,
-    grade: f32,
-    letter_grade: String,
-    categories: HashMap<String, Category>,
-    assignments: Vec<Assignment>,
+    pub(crate) grade: f32,
+    pub(crate) letter_grade: String,
+    pub(crate) categories: HashMap<String, Category>,
+    pub(crate) assignments: Vec<Assignment>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
-struct Category {
-    weight: f32,
-    points_earned: f32,
-    points_possible: f32,
+pub(crate) struct Category {
+    pub(crate) weight: f32,
+    pub(crate) points_earned: f32,
+    pub(crate) points_possible: f32,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
-struct Assignment {
-    name: String,
-    kind: String,
-    points_earned: f32,
-    points_possible: f32,
+pub(crate) struct Assignment {
+    pub(crate) name: String,
+    pub(crate) kind: String,
+    pub(crate) date: String,
+    pub(crate) due_date: String,
+    pub(crate) points_earned: f32,
+    pub(crate) points_possible: f32,
+    pub(crate) status: ScoreStatus,
+    // how many percentage points this assignment moved the class grade
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) impact: Option<f32>,
     #[serde(skip_serializing_if = "String::is_empty")]
-    notes: String,
+    pub(crate) notes: String,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ScoreStatus {
+    Graded,
+    Zero,
+    Missing,
+    PastDue,
+    #[default]
+    Pending,
+}
+
+impl ScoreStatus {
+    fn classify(
+        display_score: &str,
+        notes: &str,
+        points_earned: f32,
+        points_possible: f32,
+        due_date: &str,
+    ) -> Self {
+        // only a leading "missing", notes like "no longer missing" mean the opposite
+        let flagged = |s: &str| s.trim_start().to_lowercase().starts_with("missing");
+
+        if flagged(display_score) || flagged(notes) {
+            ScoreStatus::Missing
+        } else if points_earned == 0.0 && points_possible > 0.0 {
+            ScoreStatus::Zero
+        } else if points_earned.is_finite() {
+            ScoreStatus::Graded
+        } else if parse_date(due_date).is_some_and(|d| d < OffsetDateTime::now_utc().date()) {
+            ScoreStatus::PastDue
+        } else {
+            ScoreStatus::Pending
+        }
+    }
+
+    /// Whether the assignment is something a student still owes (or got nothing for).
+    pub(crate) fn is_outstanding(self) -> bool {
+        matches!(self, ScoreStatus::Missing | ScoreStatus::Zero | ScoreStatus::PastDue)
+    }
+}
+
+impl Class {
+    /// Recalculates the class percentage the way StudentVue does, using the category weights
+    /// when the class has any, and plain points otherwise. Unscored (NaN) assignments are skipped.
+    pub(crate) fn compute_grade<'a>(
+        &self,
+        assignments: impl IntoIterator<Item = &'a Assignment>,
+    ) -> Option<f32> {
+        let mut totals: HashMap<&str, (f32, f32)> = HashMap::new();
+        for assign in assignments {
+            if !assign.points_earned.is_finite() {
+                continue;
+            }
+
+            let total = totals.entry(assign.kind.as_str()).or_default();
+            total.0 += assign.points_earned;
+            total.1 += assign.points_possible;
+        }
+
+        if !self.categories.values().any(|c| c.weight > 0.0) {
+            let (earned, possible) = totals
+                .values()
+                .fold((0.0, 0.0), |acc, t| (acc.0 + t.0, acc.1 + t.1));
+            return (possible > 0.0).then(|| earned / possible * 100.0);
+        }
+
+        // categories without any graded work don't count, so renormalize the weights
+        let (mut score, mut weight) = (0.0, 0.0);
+        for (name, category) in &self.categories {
+            if let Some((earned, possible)) = totals.get(name.as_str()).filter(|t| t.1 > 0.0) {
+                score += category.weight * earned / possible;
+                weight += category.weight;
+            }
+        }
+
+        (weight > 0.0).then(|| score / weight * 100.0)
+    }
+
+    /// The computed grade if the assignment at `idx` had scored `points_earned` instead.
+    pub(crate) fn grade_with(&self, idx: usize, points_earned: f32) -> Option<f32> {
+        let mut replaced = self.assignments[idx].clone();
+        replaced.points_earned = points_earned;
+
+        self.compute_grade(
+            self.assignments
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != idx)
+                .map(|(_, a)| a)
+                .chain(std::iter::once(&replaced)),
+        )
+    }
+
+    fn with_impacts(mut self) -> Self {
+        let impacts: Vec<Option<f32>> = (0..self.assignments.len())
+            .map(|idx| {
+                let assign = &self.assignments[idx];
+                if !assign.points_earned.is_finite() {
+                    return None;
+                }
+
+                let with = self.grade_with(idx, assign.points_earned)?;
+                let without = self.grade_with(idx, f32::NAN)?;
+                Some(with - without)
+            })
+            .collect();
+
+        for (assign, impact) in self.assignments.iter_mut().zip(impacts) {
+            assign.impact = impact;
+        }
+
+        self
+    }
+}
+
+/// Letter grades and the percentage each one starts at, best first.
+pub(crate) const LETTER_SCALE: [(&str, f32); 5] =
+    [("A", 89.5), ("B", 79.5), ("C", 69.5), ("D", 59.5), ("E", 0.0)];
+
+pub(crate) fn letter_grade(grade: f32) -> &'static str {
+    if !grade.is_finite() {
+        return "N/A";
+    }
+
+    LETTER_SCALE
+        .iter()
+        .find(|(_, min)| grade >= *min)
+        .map_or("E", |(letter, _)| letter)
+}
+
+/// A course identifier that stays the same across reporting periods. Titles usually end with
+/// the district course code, e.g. `English 10 (EN1234)`; otherwise fall back to the period.
+pub(crate) fn course_id(title: &str, period: &str) -> String {
+    title
+        .trim_end()
+        .strip_suffix(')')
+        .and_then(|t| t.rsplit_once('('))
+        .map(|(_, code)| code.trim().to_string())
+        .filter(|code| !code.is_empty())
+        .unwrap_or_else(|| format!("{}-{}", period.trim(), title.trim()))
 }
 
 impl TryFrom<Gradebook> for Response {
@@ -104,8 +337,12 @@ impl TryFrom<Gradebook> for Response {
                     // necessary for some odd classes ig?
                     let Some(mark) = c.marks.mark else {
                         return Ok::<_, GradebookError>(Class {
+                            id: course_id(&c.title, &c.period),
                             name: c.title,
+                            period: c.period,
+                            room: c.room,
                             teacher: c.staff,
+                            teacher_email: c.staff_email,
                             grade: 0.0,
                             category: c.image_type,
                             letter_grade: "N/A".to_string(),
@@ -118,15 +355,7 @@ impl TryFrom<Gradebook> for Response {
                     let mut lg = mark.calculated_score_string;
 
                     if lg.chars().any(char::is_numeric) {
-                        lg = match grade {
-                            x if x >= 89.5 => "A",
-                            x if x >= 79.5 => "B",
-                            x if x >= 69.5 => "C",
-                            x if x >= 59.5 => "D",
-                            x if x.is_finite() => "E",
-                            _ => "N/A",
-                        }
-                        .to_string();
+                        lg = letter_grade(grade).to_string();
                     }
 
                     let mut categories = HashMap::new();
@@ -189,24 +418,43 @@ impl TryFrom<Gradebook> for Response {
                                 .replace("&gt;", ">")
                         }
 
+                        let status = ScoreStatus::classify(
+                            assign.display_score.as_deref().unwrap_or_default(),
+                            &assign.notes,
+                            points_earned,
+                            points_possible,
+                            &assign.due_date,
+                        );
+
                         assignments.push(Assignment {
                             name: unescape_xml(assign.measure),
                             kind: assign.assignment_type,
+                            date: assign.date,
+                            due_date: assign.due_date,
                             points_earned,
                             points_possible,
+                            status,
+                            impact: None,
                             notes: assign.notes,
                         })
                     }
 
-                    Ok::<_, GradebookError>(Class {
-                        name: c.title,
-                        teacher: c.staff,
-                        grade,
-                        category: c.image_type,
-                        letter_grade: lg,
-                        assignments,
-                        categories,
-                    })
+                    Ok::<_, GradebookError>(
+                        Class {
+                            id: course_id(&c.title, &c.period),
+                            name: c.title,
+                            period: c.period,
+                            room: c.room,
+                            teacher: c.staff,
+                            teacher_email: c.staff_email,
+                            grade,
+                            category: c.image_type,
+                            letter_grade: lg,
+                            assignments,
+                            categories,
+                        }
+                        .with_impacts(),
+                    )
                 })
                 .collect::<Result<Vec<_>, GradebookError>>()?,
             report_period: rp as i32,
@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{api_request, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+pub async fn health_info(token: &mut AuthToken) -> Result<HealthInfo, ApiError> {
+    let params = "<HealthConditions>true</HealthConditions>\
+                  <HealthVisits>true</HealthVisits>\
+                  <HealthImmunizations>true</HealthImmunizations>"
+        .to_string();
+
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default("StudentHealthInfo".to_string(), params, token),
+        token,
+    )
+    .await?;
+
+    let data: StudentHealthData = quick_xml::de::from_str(result.as_str())?;
+    Ok(data.into())
+}
+
+// Api structs
+/// Medical records are only shown under the `Full` redaction profile, otherwise every field is
+/// `None`.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct HealthInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    immunizations: Option<Vec<Immunization>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conditions: Option<Vec<Condition>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    visits: Option<Vec<Visit>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Immunization {
+    name: String,
+    doses_required: Option<u32>,
+    dates: Vec<String>,
+    compliant: bool,
+    // why the student isn't compliant, or an exemption
+    #[serde(skip_serializing_if = "String::is_empty")]
+    compliance_message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Condition {
+    name: String,
+    start_date: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    end_date: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    notes: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Visit {
+    date: String,
+    time: String,
+    reason: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    outcome: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    staff: String,
+}
+
+impl From<StudentHealthData> for HealthInfo {
+    fn from(value: StudentHealthData) -> Self {
+        HealthInfo {
+            immunizations: Some(
+                value
+                    .health_immunization_listings
+                    .health_immunization_listing
+                    .into_iter()
+                    .map(|x| x.into())
+                    .collect(),
+            ),
+            conditions: Some(
+                value
+                    .health_condition_listings
+                    .health_condition_listing
+                    .into_iter()
+                    .map(|x| x.into())
+                    .collect(),
+            ),
+            visits: Some(
+                value
+                    .health_visit_listings
+                    .health_visit_listing
+                    .into_iter()
+                    .map(|x| x.into())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<HealthImmunizationListing> for Immunization {
+    fn from(value: HealthImmunizationListing) -> Self {
+        Immunization {
+            name: value.name,
+            doses_required: value.num_req_doses.trim().parse().ok(),
+            dates: value
+                .immunization_dates
+                .immunization_date
+                .into_iter()
+                .map(|d| d.immunization_dt)
+                .filter(|d| !d.trim().is_empty())
+                .collect(),
+            compliant: value.compliant.eq_ignore_ascii_case("true"),
+            compliance_message: value.compliant_message,
+        }
+    }
+}
+
+impl From<HealthConditionListing> for Condition {
+    fn from(value: HealthConditionListing) -> Self {
+        Condition {
+            name: value.condition,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            notes: value.notes,
+        }
+    }
+}
+
+impl From<HealthVisitListing> for Visit {
+    fn from(value: HealthVisitListing) -> Self {
+        Visit {
+            date: value.date,
+            time: value.time,
+            reason: value.reason,
+            outcome: value.result,
+            staff: value.health_office_staff,
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct StudentHealthData {
+    #[serde(rename = "HealthVisitListings")]
+    #[serde(default)]
+    pub health_visit_listings: HealthVisitListings,
+    #[serde(rename = "HealthConditionListings")]
+    #[serde(default)]
+    pub health_condition_listings: HealthConditionListings,
+    #[serde(rename = "HealthImmunizationListings")]
+    #[serde(default)]
+    pub health_immunization_listings: HealthImmunizationListings,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct HealthVisitListings {
+    #[serde(rename = "HealthVisitListing")]
+    #[serde(default)]
+    pub health_visit_listing: Vec<HealthVisitListing>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HealthVisitListing {
+    #[serde(rename = "@Date")]
+    #[serde(default)]
+    pub date: String,
+    #[serde(rename = "@Time")]
+    #[serde(default)]
+    pub time: String,
+    #[serde(rename = "@Reason")]
+    #[serde(default)]
+    pub reason: String,
+    #[serde(rename = "@Result")]
+    #[serde(default)]
+    pub result: String,
+    #[serde(rename = "@HealthOfficeStaff")]
+    #[serde(default)]
+    pub health_office_staff: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct HealthConditionListings {
+    #[serde(rename = "HealthConditionListing")]
+    #[serde(default)]
+    pub health_condition_listing: Vec<HealthConditionListing>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HealthConditionListing {
+    #[serde(rename = "@Condition")]
+    #[serde(default)]
+    pub condition: String,
+    #[serde(rename = "@StartDate")]
+    #[serde(default)]
+    pub start_date: String,
+    #[serde(rename = "@EndDate")]
+    #[serde(default)]
+    pub end_date: String,
+    #[serde(rename = "@Notes")]
+    #[serde(default)]
+    pub notes: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct HealthImmunizationListings {
+    #[serde(rename = "HealthImmunizationListing")]
+    #[serde(default)]
+    pub health_immunization_listing: Vec<HealthImmunizationListing>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HealthImmunizationListing {
+    #[serde(rename = "@Name")]
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "@NumReqDoses")]
+    #[serde(default)]
+    pub num_req_doses: String,
+    #[serde(rename = "@Compliant")]
+    #[serde(default)]
+    pub compliant: String,
+    #[serde(rename = "@CompliantMessage")]
+    #[serde(default)]
+    pub compliant_message: String,
+    #[serde(rename = "ImmunizationDates")]
+    #[serde(default)]
+    pub immunization_dates: ImmunizationDates,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ImmunizationDates {
+    #[serde(rename = "ImmunizationDate")]
+    #[serde(default)]
+    pub immunization_date: Vec<ImmunizationDate>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImmunizationDate {
+    #[serde(rename = "@ImmunizationDt")]
+    #[serde(default)]
+    pub immunization_dt: String,
+}
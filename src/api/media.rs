@@ -0,0 +1,243 @@
+use std::io::Cursor;
+
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use image::codecs::jpeg::JpegEncoder;
+
+use crate::api::ApiError;
+
+/// Detects an image format from its magic bytes, since StudentVue doesn't say what it sent.
+pub fn sniff_image(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'B', b'M', ..] => Some("image/bmp"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// The MIME type of a file. Formats with unambiguous magic bytes win over the file name,
+/// since names can lie; everything else goes by extension.
+pub fn mime_type(file_name: &str, bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"%PDF-") {
+        return "application/pdf";
+    }
+    if let Some(mime) = sniff_image(bytes) {
+        return mime;
+    }
+
+    let ext = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.trim().to_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "webp" => "image/webp",
+        "tif" | "tiff" => "image/tiff",
+        "txt" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "rtf" => "application/rtf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "zip" => "application/zip",
+        _ if bytes.starts_with(b"PK\x03\x04") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Whether browsers can safely show a file in the page instead of downloading it.
+pub fn is_inline(mime: &str) -> bool {
+    mime == "application/pdf" || (mime.starts_with("image/") && mime != "image/tiff")
+}
+
+/// A `Content-Disposition` value per RFC 6266: an ASCII-only `filename` for old clients and an
+/// RFC 5987 encoded `filename*` with the real name.
+pub fn content_disposition(disposition: &str, file_name: &str) -> HeaderValue {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+
+    let mut encoded = String::new();
+    for byte in file_name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(byte as char),
+            b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded += &format!("%{byte:02X}"),
+        }
+    }
+
+    let value = format!("{disposition}; filename=\"{fallback}\"; filename*=UTF-8''{encoded}");
+    // everything above is visible ASCII, but never panic over a file name
+    HeaderValue::from_str(&value).unwrap_or(HeaderValue::from_static("attachment"))
+}
+
+/// Parses a single `Range: bytes=...` header into an inclusive byte range. `Ok(None)` means the
+/// whole file should be sent, and `Err` that the range can't be satisfied.
+pub fn byte_range(range: Option<&HeaderValue>, len: usize) -> Result<Option<(usize, usize)>, ()> {
+    let Some(spec) = range
+        .and_then(|r| r.to_str().ok())
+        .and_then(|r| r.trim().strip_prefix("bytes="))
+    else {
+        return Ok(None);
+    };
+
+    // multiple ranges are allowed to be answered with the whole file
+    if spec.contains(',') {
+        return Ok(None);
+    }
+
+    let (start, end) = spec.split_once('-').ok_or(())?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: usize = suffix.parse().map_err(|_| ())?;
+            if suffix == 0 {
+                return Err(());
+            }
+            (len.saturating_sub(suffix), len.checked_sub(1).ok_or(())?)
+        }
+        (start, "") => (
+            start.parse().map_err(|_| ())?,
+            len.checked_sub(1).ok_or(())?,
+        ),
+        (start, end) => {
+            let end: usize = end.parse().map_err(|_| ())?;
+            (
+                start.parse().map_err(|_| ())?,
+                end.min(len.saturating_sub(1)),
+            )
+        }
+    };
+
+    if start >= len || start > end {
+        return Err(());
+    }
+
+    Ok(Some((start, end)))
+}
+
+/// A file sent back as is. The content type comes from the name and contents, and the
+/// request's `Range` header is honoured so viewers can seek.
+pub struct Binary {
+    pub file_name: String,
+    pub data: Bytes,
+    pub range: Option<HeaderValue>,
+}
+
+impl Binary {
+    pub fn new(file_name: String, data: impl Into<Bytes>) -> Self {
+        Binary {
+            file_name,
+            data: data.into(),
+            range: None,
+        }
+    }
+
+    pub fn with_range(mut self, range: Option<&HeaderValue>) -> Self {
+        self.range = range.cloned();
+        self
+    }
+}
+
+impl IntoResponse for Binary {
+    fn into_response(self) -> Response {
+        let mime = mime_type(&self.file_name, &self.data);
+        let disposition = if is_inline(mime) {
+            "inline"
+        } else {
+            "attachment"
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(mime));
+        headers.insert(
+            header::CONTENT_DISPOSITION,
+            content_disposition(disposition, &self.file_name),
+        );
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+
+        ranged_response(self.range.as_ref(), headers, self.data)
+    }
+}
+
+/// Sends `data` with `headers`, honouring a `Range` request header. These responses are never
+/// compressed, see `compressible`.
+pub fn ranged_response(
+    range: Option<&HeaderValue>,
+    mut headers: HeaderMap,
+    data: Bytes,
+) -> Response {
+    let len = data.len();
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    match byte_range(range, len) {
+        Ok(None) => (StatusCode::OK, headers, Body::from(data)).into_response(),
+        Ok(Some((start, end))) => {
+            headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes {start}-{end}/{len}")).unwrap(),
+            );
+            let part = data.slice(start..=end);
+            (StatusCode::PARTIAL_CONTENT, headers, Body::from(part)).into_response()
+        }
+        Err(()) => {
+            headers.remove(header::CONTENT_DISPOSITION);
+            headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{len}")).unwrap(),
+            );
+            (StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response()
+        }
+    }
+}
+
+/// Whether the compression layer may encode a response. Byte ranges refer to the file as is, so
+/// anything that accepts range requests is sent uncompressed.
+pub fn compressible(headers: &HeaderMap) -> bool {
+    !headers.contains_key(header::ACCEPT_RANGES)
+}
+
+pub fn image_extension(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/bmp" => "bmp",
+        "image/webp" => "webp",
+        _ => "bin",
+    }
+}
+
+/// Scales an image down to fit in a `size` x `size` square, keeping its aspect ratio.
+/// Thumbnails are always JPEG, they're photos and it keeps them small.
+pub fn thumbnail(bytes: &[u8], size: u32) -> Result<Vec<u8>, ApiError> {
+    let img = image::load_from_memory(bytes).map_err(|_| ApiError::Image)?;
+
+    let mut out = Vec::new();
+    img.thumbnail(size, size)
+        .to_rgb8()
+        .write_with_encoder(JpegEncoder::new_with_quality(Cursor::new(&mut out), 85))
+        .map_err(|_| ApiError::Image)?;
+
+    Ok(out)
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use tokio::sync::OnceCell;
+
+use crate::api::ApiError;
+use crate::crypto::AuthToken;
+
+type Entry<V> = (Instant, Arc<OnceCell<V>>);
+
+/// A short lived, in-memory cache for upstream responses. Concurrent lookups of the same key
+/// share one fetch, and a failed fetch isn't cached so the next caller retries it.
+/// Expired entries are swept every `ttl`, so an idle cache doesn't hold on to them.
+pub struct Memo<K, V> {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<K, Entry<V>>>>,
+}
+
+impl<K: Eq + Hash + Send + 'static, V: Clone + Send + Sync + 'static> Memo<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        let entries = Arc::default();
+        // the memos are created lazily from a handler, but don't panic outside a runtime
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(sweep(Arc::downgrade(&entries), ttl));
+        }
+
+        Memo { ttl, entries }
+    }
+
+    pub async fn get_or_try_init<E>(
+        &self,
+        key: K,
+        fetch: impl Future<Output = Result<V, E>>,
+    ) -> Result<V, E> {
+        let cell = {
+            let mut entries = self.entries.lock().unwrap();
+            let now = Instant::now();
+            evict(&mut entries, now, self.ttl);
+            entries
+                .entry(key)
+                .or_insert_with(|| (now, Arc::default()))
+                .1
+                .clone()
+        };
+
+        cell.get_or_try_init(|| fetch).await.cloned()
+    }
+}
+
+/// Downloaded files, by who asked and the file's id. Viewers seek with range requests, so a
+/// file is kept for a few minutes rather than refetched for each one.
+pub struct Downloads<V>(Memo<(String, String), V>);
+
+impl<V: Clone + Send + Sync + 'static> Downloads<V> {
+    pub fn new() -> Self {
+        Downloads(Memo::new(Duration::from_secs(60 * 5)))
+    }
+
+    pub async fn get_or_fetch(
+        &self,
+        token: &mut AuthToken,
+        gu: String,
+        fetch: impl AsyncFnOnce(&mut AuthToken, String) -> Result<V, ApiError>,
+    ) -> Result<V, ApiError> {
+        let key = (token.identity(), gu.clone());
+        self.0.get_or_try_init(key, fetch(token, gu)).await
+    }
+}
+
+fn evict<K, V>(entries: &mut HashMap<K, Entry<V>>, now: Instant, ttl: Duration) {
+    entries.retain(|_, (at, _)| now.duration_since(*at) < ttl);
+}
+
+async fn sweep<K, V>(entries: Weak<Mutex<HashMap<K, Entry<V>>>>, ttl: Duration) {
+    let mut interval = tokio::time::interval(ttl);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        // stop once the memo itself is gone
+        let Some(entries) = entries.upgrade() else {
+            return;
+        };
+        evict(&mut entries.lock().unwrap(), Instant::now(), ttl);
+    }
+}
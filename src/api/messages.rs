@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::documents::base64;
+use crate::api::sanitize::{plain_text, sanitize_html};
+use crate::api::{api_request, escape_attr, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+pub async fn list_messages(token: &mut AuthToken) -> Result<Vec<Message>, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default("GetPXPMessages".to_string(), String::new(), token),
+        token,
+    )
+    .await?;
+
+    let data: PXPMessagesData = quick_xml::de::from_str(result.as_str())?;
+    Ok(data
+        .message_listings
+        .message_listing
+        .into_iter()
+        .map(|x| x.into())
+        .collect())
+}
+
+pub async fn mark_read(token: &mut AuthToken, id: &str, kind: &str) -> Result<(), ApiError> {
+    let params = format!(
+        "<MessageListing xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         ID=\"{}\" Type=\"{}\" MarkAsRead=\"true\"></MessageListing>",
+        escape_attr(id),
+        escape_attr(kind),
+    );
+
+    api_request(
+        ProcessWebServiceRequest::ck_default("UpdatePXPMessage".to_string(), params, token),
+        token,
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_attachment(token: &mut AuthToken, gu: &str) -> Result<AttachmentData, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default(
+            "SynergyMailGetAttachment".to_string(),
+            format!("<SmAttachmentGU>{}</SmAttachmentGU>", escape_attr(gu)),
+            token,
+        ),
+        token,
+    )
+    .await?;
+
+    let attachment: AttachmentXML = quick_xml::de::from_str(result.as_str())?;
+    Ok(attachment.into())
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Message {
+    id: String,
+    // needed to mark the message as read
+    kind: String,
+    from: String,
+    subject: String,
+    date: String,
+    read: bool,
+    // sanitized HTML
+    body: String,
+    attachments: Vec<Attachment>,
+}
+
+impl From<MessageListing> for Message {
+    fn from(value: MessageListing) -> Self {
+        let subject = if value.subject_no_html.is_empty() {
+            plain_text(&value.subject)
+        } else {
+            value.subject_no_html
+        };
+
+        Message {
+            id: value.id,
+            kind: value.message_type,
+            from: value.from,
+            subject,
+            date: value.begin_date,
+            read: value.read.eq_ignore_ascii_case("true"),
+            body: sanitize_html(&value.content),
+            attachments: value
+                .attachment_datas
+                .attachment_data
+                .into_iter()
+                .map(|x| x.into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Attachment {
+    name: String,
+    gu: String,
+}
+
+impl From<AttachmentData_> for Attachment {
+    fn from(value: AttachmentData_) -> Self {
+        Attachment {
+            name: value.attachment_name,
+            gu: value.sm_attachment_gu,
+        }
+    }
+}
+
+pub struct AttachmentData {
+    pub file_name: String,
+    pub file_data: Vec<u8>,
+}
+
+impl From<AttachmentXML> for AttachmentData {
+    fn from(value: AttachmentXML) -> Self {
+        AttachmentData {
+            file_name: if value.document_name.is_empty() {
+                "attachment".to_string()
+            } else {
+                value.document_name
+            },
+            file_data: value.base64_code,
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct PXPMessagesData {
+    #[serde(rename = "MessageListings")]
+    #[serde(default)]
+    pub message_listings: MessageListings,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct MessageListings {
+    #[serde(rename = "MessageListing")]
+    #[serde(default)]
+    pub message_listing: Vec<MessageListing>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MessageListing {
+    #[serde(rename = "@ID")]
+    #[serde(default)]
+    pub id: String,
+    #[serde(rename = "@Type")]
+    #[serde(default)]
+    pub message_type: String,
+    #[serde(rename = "@BeginDate")]
+    #[serde(default)]
+    pub begin_date: String,
+    #[serde(rename = "@Subject")]
+    #[serde(default)]
+    pub subject: String,
+    #[serde(rename = "@SubjectNoHTML")]
+    #[serde(default)]
+    pub subject_no_html: String,
+    #[serde(rename = "@Content")]
+    #[serde(default)]
+    pub content: String,
+    #[serde(rename = "@Read")]
+    #[serde(default)]
+    pub read: String,
+    #[serde(rename = "@From")]
+    #[serde(default)]
+    pub from: String,
+    #[serde(rename = "@Module")]
+    #[serde(default)]
+    pub module: String,
+    #[serde(rename = "AttachmentDatas")]
+    #[serde(default)]
+    pub attachment_datas: AttachmentDatas,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct AttachmentDatas {
+    #[serde(rename = "AttachmentData")]
+    #[serde(default)]
+    pub attachment_data: Vec<AttachmentData_>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AttachmentData_ {
+    #[serde(rename = "@AttachmentName")]
+    #[serde(default)]
+    pub attachment_name: String,
+    #[serde(rename = "@SmAttachmentGU")]
+    #[serde(default)]
+    pub sm_attachment_gu: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AttachmentXML {
+    #[serde(rename = "@DocumentName")]
+    #[serde(default)]
+    pub document_name: String,
+    #[serde(rename = "Base64Code")]
+    #[serde(deserialize_with = "base64")]
+    pub base64_code: Vec<u8>,
+}
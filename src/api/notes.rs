@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::gradebook::{course_id, Class};
+use crate::api::sanitize::sanitize_html;
+use crate::api::{api_request, parse_date, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+/// Homework and class notes grouped by course, newest first. Courses are matched up with the
+/// gradebook's `classes` so they share its ids.
+pub async fn list_notes(
+    token: &mut AuthToken,
+    classes: &[Class],
+) -> Result<Vec<CourseNotes>, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default("StudentHWNotes".to_string(), String::new(), token),
+        token,
+    )
+    .await?;
+
+    let data: StudentHWNotes = quick_xml::de::from_str(result.as_str())?;
+
+    let mut courses: Vec<CourseNotes> = Vec::new();
+    for note in data.student_hw_note_datas.student_hw_note_data {
+        let id = class_id(&note, classes);
+        let idx = match courses.iter().position(|c| c.id == id) {
+            Some(idx) => idx,
+            None => {
+                courses.push(CourseNotes {
+                    id,
+                    course: note.course_title.clone(),
+                    period: note.period.clone(),
+                    teacher: note.teacher_name.clone(),
+                    notes: Vec::new(),
+                });
+                courses.len() - 1
+            }
+        };
+        courses[idx].notes.push(note.into());
+    }
+
+    for course in &mut courses {
+        course
+            .notes
+            .sort_by_key(|n| std::cmp::Reverse(parse_date(&n.date)));
+    }
+
+    Ok(courses)
+}
+
+/// The gradebook id of the class a note belongs to. Note titles don't always carry the course
+/// code the gradebook's do, so this falls back to the period and title.
+fn class_id(note: &StudentHWNoteData, classes: &[Class]) -> String {
+    let id = course_id(&note.course_title, &note.period);
+    if classes.iter().any(|c| c.id == id) {
+        return id;
+    }
+
+    let title = without_code(&note.course_title);
+    let same_title = |c: &Class| without_code(&c.name).eq_ignore_ascii_case(title);
+    let in_period: Vec<&Class> = classes
+        .iter()
+        .filter(|c| c.period.trim() == note.period.trim())
+        .collect();
+    let titled: Vec<&Class> = classes.iter().filter(|c| same_title(c)).collect();
+
+    let class = in_period.iter().copied().find(|c| same_title(c)).or(
+        match (in_period.as_slice(), titled.as_slice()) {
+            // the only class that period, or the only one with that title
+            ([only], _) | (_, [only]) => Some(*only),
+            _ => None,
+        },
+    );
+
+    class.map_or(id, |c| c.id.clone())
+}
+
+// `English 10 (EN1234)` -> `English 10`
+fn without_code(title: &str) -> &str {
+    title
+        .trim_end()
+        .strip_suffix(')')
+        .and_then(|t| t.rsplit_once('('))
+        .map_or(title, |(t, _)| t)
+        .trim()
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CourseNotes {
+    /// Same as the gradebook's class id
+    pub id: String,
+    course: String,
+    period: String,
+    teacher: String,
+    notes: Vec<Note>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Note {
+    gu: String,
+    date: String,
+    // e.g. "Homework" or "Class Notes"
+    kind: String,
+    // sanitized HTML
+    content: String,
+}
+
+impl From<StudentHWNoteData> for Note {
+    fn from(value: StudentHWNoteData) -> Self {
+        Note {
+            gu: value.gu,
+            date: value.date,
+            kind: value.note_type,
+            content: sanitize_html(&value.notes),
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct StudentHWNotes {
+    #[serde(rename = "StudentHWNoteDatas")]
+    #[serde(default)]
+    pub student_hw_note_datas: StudentHWNoteDatas,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct StudentHWNoteDatas {
+    #[serde(rename = "StudentHWNoteData")]
+    #[serde(default)]
+    pub student_hw_note_data: Vec<StudentHWNoteData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StudentHWNoteData {
+    #[serde(rename = "@GU")]
+    #[serde(default)]
+    pub gu: String,
+    #[serde(rename = "@Date")]
+    #[serde(default)]
+    pub date: String,
+    #[serde(rename = "@CourseTitle")]
+    #[serde(default)]
+    pub course_title: String,
+    #[serde(rename = "@Period")]
+    #[serde(default)]
+    pub period: String,
+    #[serde(rename = "@TeacherName")]
+    #[serde(default)]
+    pub teacher_name: String,
+    #[serde(rename = "@NoteType")]
+    #[serde(default)]
+    pub note_type: String,
+    #[serde(rename = "@Notes")]
+    #[serde(default)]
+    pub notes: String,
+}
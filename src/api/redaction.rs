@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::AuthToken;
+
+/// How much personal information responses may contain, least restrictive first.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// Everything StudentVue returns
+    #[default]
+    Full,
+    /// No address, birth date, medical or custom fields, and phone numbers are masked
+    Limited,
+    /// Only what's needed to identify the student: no contact, medical or transport details
+    Minimal,
+}
+
+impl std::str::FromStr for Profile {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "full" => Ok(Profile::Full),
+            "limited" => Ok(Profile::Limited),
+            "minimal" => Ok(Profile::Minimal),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Profile {
+    /// The operator's profile from `REDACTION_PROFILE`, `Full` when unset.
+    pub fn server() -> Profile {
+        std::env::var("REDACTION_PROFILE")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// The strictest of the operator's profile and the one the token was scoped to.
+    pub fn effective(token: &AuthToken) -> Profile {
+        Profile::server().max(token.profile)
+    }
+}
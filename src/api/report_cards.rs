@@ -0,0 +1,156 @@
+use axum::body::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::api::documents::base64;
+use crate::api::memo::Downloads;
+use crate::api::{api_request, escape_attr, ApiError, ProcessWebServiceRequest};
+use crate::crypto::AuthToken;
+
+pub async fn list_report_cards(token: &mut AuthToken) -> Result<Vec<ReportCard>, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default(
+            "GetReportCardInitialData".to_string(),
+            String::new(),
+            token,
+        ),
+        token,
+    )
+    .await?;
+
+    let data: RCReportingPeriodData = quick_xml::de::from_str(result.as_str())?;
+    Ok(data
+        .rc_reporting_periods
+        .rc_reporting_period
+        .into_iter()
+        .map(|x| x.into())
+        .collect())
+}
+
+pub async fn get_report_card(
+    token: &mut AuthToken,
+    gu: String,
+) -> Result<ReportCardData, ApiError> {
+    let result = api_request(
+        ProcessWebServiceRequest::ck_default(
+            "GetReportCardDocumentData".to_string(),
+            format!("<DocumentGU>{}</DocumentGU>", escape_attr(&gu)),
+            token,
+        ),
+        token,
+    )
+    .await?;
+
+    let data: DocumentData = quick_xml::de::from_str(result.as_str())?;
+    Ok(data.into())
+}
+
+lazy_static::lazy_static! {
+    static ref REPORT_CARDS: Downloads<ReportCardData> = Downloads::new();
+}
+
+/// Like `get_report_card`, but keeps the file around for a few minutes.
+pub async fn cached_report_card(
+    token: &mut AuthToken,
+    gu: String,
+) -> Result<ReportCardData, ApiError> {
+    REPORT_CARDS.get_or_fetch(token, gu, get_report_card).await
+}
+
+// Api structs
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportCard {
+    period: String,
+    period_gu: String,
+    end_date: String,
+    // set when the report card isn't published yet, e.g. "Report card not available"
+    #[serde(skip_serializing_if = "String::is_empty")]
+    message: String,
+    // empty when there's nothing to download
+    gu: String,
+}
+
+impl From<RCReportingPeriod> for ReportCard {
+    fn from(value: RCReportingPeriod) -> Self {
+        ReportCard {
+            period: value.reporting_period_name,
+            period_gu: value.reporting_period_gu,
+            end_date: value.end_date,
+            message: value.message,
+            gu: value.document_gu,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ReportCardData {
+    pub file_name: String,
+    pub file_data: Bytes,
+}
+
+impl From<DocumentData> for ReportCardData {
+    fn from(value: DocumentData) -> Self {
+        let file_name = [value.file_name, value.doc_file_name]
+            .into_iter()
+            .find(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| "report_card.pdf".to_string());
+
+        ReportCardData {
+            file_name,
+            file_data: value.base64_code.into(),
+        }
+    }
+}
+
+// XML structs
+
+#[derive(Serialize, Deserialize)]
+pub struct RCReportingPeriodData {
+    #[serde(rename = "RCReportingPeriods")]
+    #[serde(default)]
+    pub rc_reporting_periods: RCReportingPeriods,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct RCReportingPeriods {
+    #[serde(rename = "RCReportingPeriod")]
+    #[serde(default)]
+    pub rc_reporting_period: Vec<RCReportingPeriod>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RCReportingPeriod {
+    #[serde(rename = "@ReportingPeriodGU")]
+    #[serde(default)]
+    pub reporting_period_gu: String,
+    #[serde(rename = "@ReportingPeriodName")]
+    #[serde(default)]
+    pub reporting_period_name: String,
+    #[serde(rename = "@EndDate")]
+    #[serde(default)]
+    pub end_date: String,
+    #[serde(rename = "@Message")]
+    #[serde(default)]
+    pub message: String,
+    #[serde(rename = "@DocumentGU")]
+    #[serde(default)]
+    pub document_gu: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DocumentData {
+    #[serde(rename = "@DocumentGU")]
+    #[serde(default)]
+    pub document_gu: String,
+    #[serde(rename = "@FileName")]
+    #[serde(default)]
+    pub file_name: String,
+    #[serde(rename = "@DocFileName")]
+    #[serde(default)]
+    pub doc_file_name: String,
+    #[serde(rename = "@DocType")]
+    #[serde(default)]
+    pub doc_type: String,
+    #[serde(rename = "Base64Code")]
+    #[serde(deserialize_with = "base64")]
+    pub base64_code: Vec<u8>,
+}
@@ -0,0 +1,287 @@
+/// Tags teachers use for formatting. Everything else is dropped, keeping its text.
+const ALLOWED: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "div",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "li",
+    "ol",
+    "p",
+    "span",
+    "strong",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Tags whose contents are never shown.
+const STRIPPED: &[&str] = &[
+    "script", "style", "iframe", "object", "embed", "head", "title", "template", "noscript",
+];
+
+/// Cleans teacher-written HTML down to basic formatting, so clients can render it without
+/// worrying about scripts, styles or tracking. All attributes are removed except `href` on
+/// links, and only web and mail links survive.
+pub fn sanitize_html(html: &str) -> String {
+    clean(html, true)
+}
+
+/// The text of some HTML with every tag dropped, for fields shown as plain text. The result
+/// isn't escaped, it must not be rendered as HTML.
+pub fn plain_text(html: &str) -> String {
+    clean(html, false)
+}
+
+fn clean(html: &str, markup: bool) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    // the stripped tag we're currently inside of, if any
+    let mut skipping: Option<&str> = None;
+
+    while let Some(start) = rest.find('<') {
+        if skipping.is_none() {
+            push_text(&mut out, &rest[..start], markup);
+        }
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let opens_tag = rest[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!');
+
+        let Some(end) = tag_end(rest).filter(|_| opens_tag) else {
+            // a lone '<' is just text
+            if skipping.is_none() {
+                out.push_str(if markup { "&lt;" } else { "<" });
+            }
+            rest = &rest[1..];
+            continue;
+        };
+
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+
+        if let Some(stripped) = skipping {
+            if closing && name == stripped {
+                skipping = None;
+            }
+            continue;
+        }
+
+        if let Some(stripped) = STRIPPED.iter().find(|s| **s == name) {
+            if !closing && !tag.ends_with('/') {
+                skipping = Some(stripped);
+            }
+            continue;
+        }
+
+        if !markup || !ALLOWED.contains(&name.as_str()) {
+            continue;
+        }
+
+        if closing {
+            out.push_str(&format!("</{name}>"));
+        } else if name == "a" {
+            match attribute(&tag[name_end..], "href").filter(|h| safe_link(h)) {
+                Some(href) => {
+                    out.push_str("<a href=\"");
+                    push_text(&mut out, &href, true);
+                    out.push_str("\" rel=\"noopener noreferrer\">");
+                }
+                None => out.push_str("<a>"),
+            }
+        } else {
+            out.push_str(&format!("<{name}>"));
+        }
+    }
+
+    if skipping.is_none() {
+        push_text(&mut out, rest, markup);
+    }
+
+    out
+}
+
+// finds the '>' closing a tag, skipping over any inside quoted attribute values
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            (None, '<') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    while let Some(idx) = rest.to_ascii_lowercase().find(name) {
+        let after = rest[idx + name.len()..].trim_start();
+        let preceded = rest[..idx]
+            .chars()
+            .last()
+            .is_none_or(|c| c.is_whitespace() || c == '/');
+
+        if let Some(value) = after.strip_prefix('=').filter(|_| preceded) {
+            let value = value.trim_start();
+            let value = match value.chars().next() {
+                Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or_default(),
+                _ => value.split_whitespace().next().unwrap_or_default(),
+            };
+            return Some(decode_entities(value));
+        }
+
+        rest = &rest[idx + name.len()..];
+    }
+    None
+}
+
+fn safe_link(href: &str) -> bool {
+    let href = href.trim().to_ascii_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| href.starts_with(scheme))
+}
+
+// text is escaped again after decoding, so entities in the source can't smuggle in markup
+fn push_text(out: &mut String, text: &str, escape: bool) {
+    if !escape {
+        out.push_str(&decode_entities(text));
+        return;
+    }
+
+    for c in decode_entities(text).chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", "\u{a0}")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plain_text, sanitize_html};
+
+    #[test]
+    fn keeps_basic_formatting() {
+        assert_eq!(
+            sanitize_html("<p class=\"x\">Read <b>ch. 4</b><br/></p>"),
+            "<p>Read <b>ch. 4</b><br></p>"
+        );
+    }
+
+    #[test]
+    fn strips_scripts_styles_and_comments() {
+        assert_eq!(
+            sanitize_html("a<script>alert(1)</script>b<style>p{}</style>c<!-- <b>x</b> -->d"),
+            "abcd"
+        );
+        assert_eq!(sanitize_html("a<SCRIPT src=x>alert(1)</ScRiPt>b"), "ab");
+        assert_eq!(sanitize_html("a<!-- never closed <script>"), "a");
+    }
+
+    #[test]
+    fn drops_unknown_tags_but_keeps_their_text() {
+        assert_eq!(
+            sanitize_html("<font color=red>due <img src=x onerror=alert(1)>Friday</font>"),
+            "due Friday"
+        );
+    }
+
+    #[test]
+    fn only_keeps_safe_links() {
+        assert_eq!(
+            sanitize_html("<a href=\"https://x.org/?a=1&amp;b=2\" onclick=\"y()\">x</a>"),
+            "<a href=\"https://x.org/?a=1&amp;b=2\" rel=\"noopener noreferrer\">x</a>"
+        );
+        assert_eq!(
+            sanitize_html("<a href=\"javascript:alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            sanitize_html("<a href=\" JaVaScRiPt:alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            sanitize_html("<a data-href=\"https://x.org\">x</a>"),
+            "<a>x</a>"
+        );
+    }
+
+    #[test]
+    fn handles_quotes_in_attributes() {
+        assert_eq!(
+            sanitize_html("<a title=\"a > b\" href='https://x.org/\"'>x</a>"),
+            "<a href=\"https://x.org/&quot;\" rel=\"noopener noreferrer\">x</a>"
+        );
+    }
+
+    #[test]
+    fn entities_cannot_smuggle_markup() {
+        assert_eq!(
+            sanitize_html("&lt;script&gt;alert(1)&lt;/script&gt;"),
+            "&lt;script&gt;alert(1)&lt;/script&gt;"
+        );
+        assert_eq!(
+            sanitize_html("<a href=\"&quot; onclick=&quot;x()\">x</a>"),
+            "<a>x</a>"
+        );
+    }
+
+    #[test]
+    fn lone_angle_brackets_are_text() {
+        assert_eq!(sanitize_html("a < b and c > d"), "a &lt; b and c &gt; d");
+    }
+
+    #[test]
+    fn plain_text_drops_all_tags() {
+        assert_eq!(
+            plain_text("<b>Field trip</b> &amp; <script>x</script>forms"),
+            "Field trip & forms"
+        );
+    }
+}
@@ -1,9 +1,27 @@
-use crate::api::{api_request, ApiError, ProcessWebServiceRequest};
+use std::time::Duration;
+
+use crate::api::media;
+use crate::api::memo::Memo;
+use crate::api::redaction::Profile;
+use crate::api::{api_request, parse_date, ApiError, ProcessWebServiceRequest};
 use crate::crypto::AuthToken;
 use crate::documents::base64;
 use serde::{Deserialize, Serialize};
 
+lazy_static::lazy_static! {
+    // one StudentInfo call carries both the info and the photo, so a client that shows both
+    // (or asks twice in a row) only costs one upstream request
+    static ref STUDENT_INFO: Memo<String, (StudentInfo, Vec<u8>)> =
+        Memo::new(Duration::from_secs(30));
+}
+
 pub async fn both(token: &mut AuthToken) -> Result<(StudentInfo, Vec<u8>), ApiError> {
+    STUDENT_INFO
+        .get_or_try_init(token.identity(), fetch_both(token))
+        .await
+}
+
+async fn fetch_both(token: &mut AuthToken) -> Result<(StudentInfo, Vec<u8>), ApiError> {
     let result = api_request(
         ProcessWebServiceRequest::ck_default("StudentInfo".to_string(), String::new(), token),
         token,
@@ -23,20 +41,140 @@ pub async fn photo(token: &mut AuthToken) -> Result<Vec<u8>, ApiError> {
     Ok(both(token).await?.1)
 }
 
-#[derive(Serialize, Deserialize)]
+lazy_static::lazy_static! {
+    // avatar lists ask for the same thumbnails over and over
+    static ref THUMBNAILS: Memo<(String, u32), Vec<u8>> =
+        Memo::new(Duration::from_secs(60 * 10));
+}
+
+// every size gets its own cache entry, so only a few are handed out
+const THUMBNAIL_SIZES: [u32; 5] = [32, 64, 128, 256, 512];
+
+/// The student's photo as a JPEG no larger than `size` pixels on either side, with `size`
+/// rounded up to the next of `THUMBNAIL_SIZES` (and down to the largest).
+pub async fn thumbnail(token: &mut AuthToken, size: u32) -> Result<Vec<u8>, ApiError> {
+    let size = THUMBNAIL_SIZES
+        .into_iter()
+        .find(|&s| s >= size)
+        .unwrap_or(THUMBNAIL_SIZES[THUMBNAIL_SIZES.len() - 1]);
+
+    THUMBNAILS
+        .get_or_try_init((token.identity(), size), async {
+            let photo = photo(token).await?;
+            // decoding and resizing is CPU heavy, keep it off the async workers
+            tokio::task::spawn_blocking(move || media::thumbnail(&photo, size))
+                .await
+                .map_err(|_| ApiError::Image)?
+        })
+        .await
+}
+
+/// Student info with the photo inlined, for clients that show both.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StudentProfile {
+    #[serde(flatten)]
+    pub info: StudentInfo,
+    // base64 encoded
+    pub photo: String,
+    pub photo_type: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct StudentInfo {
     pub name: String,
     pub id: String,
     gender: String,
     grade: String,
-    address: String,
-    birth_date: String,
-    email: String,
-    phone_number: String,
-    emergency_contacts: Vec<Contact>,
-    physician: Doctor,
-    dentist: Doctor,
-    school: String
+    // the Option fields below are only None when a redaction profile removed them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    birth_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    emergency_contacts: Option<Vec<Contact>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    physician: Option<Doctor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dentist: Option<Doctor>,
+    school: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nickname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_name_goes_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    home_language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    track: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    counselor: Option<Staff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    homeroom: Option<Homeroom>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locker: Option<Locker>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    buses: Vec<Bus>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    custom_fields: Vec<FieldGroup>,
+}
+
+impl StudentInfo {
+    /// Strips or masks the fields a redaction profile doesn't allow.
+    pub fn redact(mut self, profile: Profile) -> Self {
+        match profile {
+            Profile::Full => {}
+            Profile::Limited => {
+                self.address = None;
+                self.birth_date = None;
+                self.phone_number = self.phone_number.as_deref().map(mask_phone);
+                self.physician = None;
+                self.dentist = None;
+                self.custom_fields.clear();
+
+                for contact in self.emergency_contacts.iter_mut().flatten() {
+                    for number in &mut contact.phone_numbers {
+                        *number = mask_phone(number);
+                    }
+                }
+                if let Some(locker) = &mut self.locker {
+                    locker.combination = None;
+                }
+            }
+            Profile::Minimal => {
+                self.address = None;
+                self.birth_date = None;
+                self.email = None;
+                self.phone_number = None;
+                self.emergency_contacts = None;
+                self.physician = None;
+                self.dentist = None;
+                self.locker = None;
+                self.buses.clear();
+                self.custom_fields.clear();
+            }
+        }
+
+        self
+    }
+}
+
+// keeps the last four digits, so a number can still be recognized. Blanks and values without
+// digits (e.g. "none") are left alone, and numbers too short to keep any digits are hidden whole
+fn mask_phone(number: &str) -> String {
+    let digits: Vec<char> = number.chars().filter(char::is_ascii_digit).collect();
+    match digits.len() {
+        0 => number.to_string(),
+        1..=4 => "***".to_string(),
+        n => format!("***-***-{}", digits[n - 4..].iter().collect::<String>()),
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
 }
 
 impl From<StudentInfo_> for StudentInfo {
@@ -46,24 +184,178 @@ impl From<StudentInfo_> for StudentInfo {
             id: value.perm_id,
             gender: value.gender,
             grade: value.grade,
-            address: value.address.replace("<br>", "\n").to_string(),
-            birth_date: value.birth_date,
-            email: value.email,
-            phone_number: value.phone,
-            emergency_contacts: value
-                .emergency_contacts
-                .emergency_contact
+            address: Some(value.address.replace("<br>", "\n").to_string()),
+            birth_date: Some(value.birth_date),
+            email: Some(value.email),
+            phone_number: Some(value.phone),
+            emergency_contacts: Some(
+                value
+                    .emergency_contacts
+                    .emergency_contact
+                    .into_iter()
+                    .map(|x| x.into())
+                    .collect(),
+            ),
+            physician: Some(value.physician.into()),
+            dentist: Some(value.dentist.into()),
+            school: value.current_school,
+            nickname: non_empty(value.nick_name),
+            last_name_goes_by: value.last_name_goes_by.text.and_then(non_empty),
+            home_language: value.home_language.text.and_then(non_empty),
+            track: value.track.text.and_then(non_empty),
+            counselor: non_empty(value.counselor_name).map(|name| Staff {
+                name,
+                email: value.counselor_email,
+            }),
+            homeroom: non_empty(value.home_room).map(|room| Homeroom {
+                room,
+                teacher: value.home_room_tch,
+                teacher_email: value.home_room_tch_email,
+            }),
+            locker: value
+                .locker_info_records
+                .student_locker_info_record
+                .map(|x| x.into()),
+            buses: value
+                .student_bus_assignments
+                .student_bus_assignment
                 .into_iter()
                 .map(|x| x.into())
                 .collect(),
-            physician: value.physician.into(),
-            dentist: value.dentist.into(),
-            school: value.current_school
+            custom_fields: value
+                .user_defined_group_boxes
+                .user_defined_group_box
+                .into_iter()
+                .map(|x| x.into())
+                .filter(|x: &FieldGroup| !x.fields.is_empty())
+                .collect(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
+struct Staff {
+    name: String,
+    email: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Homeroom {
+    room: String,
+    teacher: String,
+    teacher_email: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Locker {
+    number: String,
+    location: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    combination: Option<String>,
+}
+
+impl From<StudentLockerInfoRecord> for Locker {
+    fn from(value: StudentLockerInfoRecord) -> Self {
+        Locker {
+            number: value.locker_number,
+            location: value.location,
+            combination: Some(value.current_combination),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Bus {
+    route: String,
+    bus_number: String,
+    stop: String,
+    direction: String,
+    pick_up_time: String,
+    drop_off_time: String,
+}
+
+impl From<StudentBusAssignment> for Bus {
+    fn from(value: StudentBusAssignment) -> Self {
+        Bus {
+            route: value.bus_route_name,
+            bus_number: value.bus_number,
+            stop: value.bus_stop_name,
+            direction: value.direction,
+            pick_up_time: value.pick_up_time,
+            drop_off_time: value.drop_off_time,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct FieldGroup {
+    label: String,
+    fields: Vec<Field>,
+}
+
+impl From<UserDefinedGroupBox> for FieldGroup {
+    fn from(value: UserDefinedGroupBox) -> Self {
+        FieldGroup {
+            label: value.group_box_label,
+            fields: value
+                .user_defined_items
+                .into_iter()
+                .flat_map(|x| x.user_defined_item)
+                .filter(|x| !x.value.trim().is_empty())
+                .map(|x| x.into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Field {
+    label: String,
+    #[serde(flatten)]
+    value: FieldValue,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+enum FieldValue {
+    Text(String),
+    Number(f64),
+    // ISO formatted
+    Date(String),
+    Boolean(bool),
+}
+
+impl From<UserDefinedItem> for Field {
+    fn from(value: UserDefinedItem) -> Self {
+        let raw = value.value.trim();
+        let kind = value.item_type.to_lowercase();
+
+        // districts name these types inconsistently, so anything unparseable stays text
+        let typed = if kind.contains("date") {
+            parse_date(raw).map(|d| FieldValue::Date(d.to_string()))
+        } else if ["int", "decimal", "number", "numeric", "money", "currency"]
+            .iter()
+            .any(|t| kind.contains(t))
+        {
+            raw.replace([',', '$'], "").parse().ok().map(FieldValue::Number)
+        } else if ["bool", "yesno", "checkbox"].iter().any(|t| kind.contains(t)) {
+            match raw.to_lowercase().as_str() {
+                "y" | "yes" | "true" | "1" => Some(FieldValue::Boolean(true)),
+                "n" | "no" | "false" | "0" => Some(FieldValue::Boolean(false)),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        Field {
+            label: value.item_label,
+            value: typed.unwrap_or_else(|| FieldValue::Text(raw.to_string())),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 struct Contact {
     name: String,
     relation: String,
@@ -95,7 +387,7 @@ impl From<EmergencyContact> for Contact {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 struct Doctor {
     name: String,
     workplace: String,
@@ -223,13 +515,22 @@ pub struct StudentLockerInfoRecord {
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct LastNameGoesBy {}
+pub struct LastNameGoesBy {
+    #[serde(rename = "$text")]
+    pub text: Option<String>,
+}
 
 #[derive(Serialize, Deserialize)]
-pub struct HomeLanguage {}
+pub struct HomeLanguage {
+    #[serde(rename = "$text")]
+    pub text: Option<String>,
+}
 
 #[derive(Serialize, Deserialize)]
-pub struct Track {}
+pub struct Track {
+    #[serde(rename = "$text")]
+    pub text: Option<String>,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct EmergencyContacts {
@@ -280,6 +581,7 @@ pub struct Dentist {
 #[derive(Serialize, Deserialize)]
 pub struct UserDefinedGroupBoxes {
     #[serde(rename = "UserDefinedGroupBox")]
+    #[serde(default)]
     pub user_defined_group_box: Vec<UserDefinedGroupBox>,
 }
 
@@ -288,12 +590,14 @@ pub struct UserDefinedGroupBox {
     #[serde(rename = "@GroupBoxLabel")]
     pub group_box_label: String,
     #[serde(rename = "UserDefinedItems")]
+    #[serde(default)]
     pub user_defined_items: Vec<UserDefinedItems>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct UserDefinedItems {
     #[serde(rename = "UserDefinedItem")]
+    #[serde(default)]
     pub user_defined_item: Vec<UserDefinedItem>,
 }
 
@@ -314,4 +618,30 @@ pub struct UserDefinedItem {
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct StudentBusAssignments {}
+pub struct StudentBusAssignments {
+    #[serde(rename = "StudentBusAssignment")]
+    #[serde(default)]
+    pub student_bus_assignment: Vec<StudentBusAssignment>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StudentBusAssignment {
+    #[serde(rename = "@BusRouteName")]
+    #[serde(default)]
+    pub bus_route_name: String,
+    #[serde(rename = "@BusNumber")]
+    #[serde(default)]
+    pub bus_number: String,
+    #[serde(rename = "@BusStopName")]
+    #[serde(default)]
+    pub bus_stop_name: String,
+    #[serde(rename = "@Direction")]
+    #[serde(default)]
+    pub direction: String,
+    #[serde(rename = "@PickUpTime")]
+    #[serde(default)]
+    pub pick_up_time: String,
+    #[serde(rename = "@DropOffTime")]
+    #[serde(default)]
+    pub drop_off_time: String,
+}
@@ -9,12 +9,14 @@ use aes_gcm_siv::{
 };
 use axum::extract::FromRequestParts;
 use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
 use axum::http::request::Parts;
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::api::redaction::Profile;
 use crate::api::ApiError;
 
 #[derive(Error, Debug)]
@@ -79,7 +81,20 @@ pub struct AuthToken {
     #[serde(with = "string")]
     pub expiry: u128,
 
-    pub district_url: String
+    pub district_url: String,
+
+    // tokens can be narrowed to a redaction profile before handing them out
+    #[serde(default)]
+    pub profile: Profile,
+
+    // parent accounts act on behalf of one of their children, picked by its index in ChildList
+    #[serde(default)]
+    pub parent: bool,
+    #[serde(default)]
+    pub child: Option<u32>,
+    // a one-off choice from `x-child-id`, never written back into an encoded token
+    #[serde(skip)]
+    pub child_override: Option<u32>,
 }
 
 mod string {
@@ -119,6 +134,34 @@ impl AuthToken {
     pub fn is_empty(&self) -> bool {
         self.username.is_empty() || self.password.is_empty()
     }
+
+    /// Encrypts the token into the base64 form clients send back as a bearer token.
+    pub fn encode(&self) -> Result<String, ApiError> {
+        let enc = serde_json::to_string(self).map_err(|_| ApiError::Unknown)?;
+        Ok(BASE64_STANDARD.encode(create_token(enc)?))
+    }
+
+    /// Whose data this token fetches, used to key caches. Includes the password so a cached
+    /// response is never handed to someone who only knows the username, and the selected child
+    /// so siblings never see each other's data.
+    pub fn identity(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}",
+            self.district_url,
+            self.username,
+            self.password,
+            self.child_id()
+        )
+    }
+
+    /// The `ChildIntID` sent with every request. Student accounts always use 0.
+    pub fn child_id(&self) -> u32 {
+        if self.parent {
+            self.child_override.or(self.child).unwrap_or_default()
+        } else {
+            0
+        }
+    }
 }
 
 impl<S: Send + Sync> FromRequestParts<S> for AuthToken {
@@ -133,7 +176,7 @@ impl<S: Send + Sync> FromRequestParts<S> for AuthToken {
             .map_err(|_| ApiError::InvalidCredentials)?;
 
         let split = authorization.split_once(' ');
-        match split {
+        let mut token = match split {
             Some(("Bearer", contents)) => {
                 let json = try_decrypt_token(
                     &BASE64_STANDARD
@@ -142,7 +185,7 @@ impl<S: Send + Sync> FromRequestParts<S> for AuthToken {
                 )?;
 
                 let ret = serde_json::from_str(&json).map_err(|_| ApiError::InvalidCredentials)?;
-                check_validity(ret)
+                check_validity(ret)?
             }
             Some(("Basic", contents)) => {
                 let decoded = String::from_utf8(
@@ -156,17 +199,50 @@ impl<S: Send + Sync> FromRequestParts<S> for AuthToken {
                     .split_once(':')
                     .ok_or(ApiError::InvalidCredentials)?;
 
-                Ok(AuthToken {
+                AuthToken {
                     username: username.to_string(),
                     password: password.to_string(),
                     cookie: None,
                     expiry: get_timestamp() + 1000 * 60 * 60 * 24,
                     // in the future, use this to support other districts
-                    district_url: "md-mcps-psv.edupoint.com".to_string()
-                })
+                    district_url: "md-mcps-psv.edupoint.com".to_string(),
+                    profile: Profile::Full,
+                    parent: account_type(&parts.headers)?,
+                    child: None,
+                    child_override: None,
+                }
             }
-            _ => Err(ApiError::InvalidCredentials),
+            _ => Err(ApiError::InvalidCredentials)?,
+        };
+
+        // parents can switch children per request without getting a new token
+        if let Some(child) = parts.headers.get("x-child-id") {
+            if !token.parent {
+                Err(ApiError::InvalidQuery)?
+            }
+
+            let child = child
+                .to_str()
+                .ok()
+                .and_then(|c| c.trim().parse().ok())
+                .ok_or(ApiError::InvalidQuery)?;
+            token.child_override = Some(child);
         }
+
+        Ok(token)
+    }
+}
+
+// `x-account-type: parent` logs in with a parent account, students are the default
+fn account_type(headers: &HeaderMap) -> Result<bool, ApiError> {
+    let Some(kind) = headers.get("x-account-type") else {
+        return Ok(false);
+    };
+
+    match kind.to_str().map(|k| k.trim().to_lowercase()).as_deref() {
+        Ok("parent") => Ok(true),
+        Ok("student") => Ok(false),
+        _ => Err(ApiError::InvalidQuery),
     }
 }
 
@@ -1,20 +1,28 @@
 use std::net::SocketAddr;
 
 use axum::body::Body;
-use axum::extract::Query;
+use axum::extract::{Path, Query};
 use axum::http::{header, HeaderMap, HeaderName, HeaderValue};
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::{Json, Router as AxumRouter};
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+use futures::{stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use tokio_util::io::ReaderStream;
+use tower_http::compression::predicate::{DefaultPredicate, Predicate};
 use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 
 use crate::api::documents::Document;
+use crate::api::media::Binary;
+use crate::api::redaction::Profile;
 use crate::api::school_info::SchoolInfo;
 use crate::api::student_info::StudentInfo;
-use crate::api::{documents, gradebook, school_info, student_info, ApiError};
+use crate::api::{
+    analytics, assessments, assignments, calendar, children, course_history, documents, fees,
+    gradebook, health, media, messages, notes, report_cards, school_info, student_info, ApiError,
+};
 use crate::crypto::AuthToken;
 
 #[cfg(feature = "schedule")]
@@ -61,11 +69,14 @@ pub async fn get_edu_version() -> Result<String, ApiError> {
 }
 
 type Resp<T> = Result<(HeaderMap, Json<T>), ApiError>;
+type BinResp = Result<(HeaderMap, Binary), ApiError>;
 
-async fn get_data<T: Serialize>(
+/// Runs `fetch` with the caller's token, adding a `set-token` header whenever StudentVue handed
+/// out a new cookie along the way. Every endpoint that talks to StudentVue goes through here.
+async fn with_token<T>(
     mut token: AuthToken,
     fetch: impl for<'a> AsyncFnOnce(&'a mut AuthToken) -> Result<T, ApiError>,
-) -> Resp<T> {
+) -> Result<(HeaderMap, T), ApiError> {
     if token.is_empty() {
         Err(ApiError::EmptyCredentials)?
     }
@@ -75,26 +86,246 @@ async fn get_data<T: Serialize>(
 
     let mut hm = HeaderMap::new();
     if old != token {
-        let enc = serde_json::to_string(&token).map_err(|_| ApiError::Unknown)?;
-        let tok = BASE64_STANDARD.encode(crypto::create_token(enc)?);
+        let tok = token.encode()?;
 
         hm.insert(
             HeaderName::from_static("set-token"),
-            HeaderValue::from_str(&tok).unwrap(),
+            HeaderValue::from_str(&tok).map_err(|_| ApiError::Unknown)?,
         );
     }
 
+    Ok((hm, data))
+}
+
+async fn get_data<T: Serialize>(
+    token: AuthToken,
+    fetch: impl for<'a> AsyncFnOnce(&'a mut AuthToken) -> Result<T, ApiError>,
+) -> Resp<T> {
+    let (hm, data) = with_token(token, fetch).await?;
     Ok((hm, Json(data)))
 }
 
+async fn get_binary(
+    token: AuthToken,
+    fetch: impl for<'a> AsyncFnOnce(&'a mut AuthToken) -> Result<Binary, ApiError>,
+) -> BinResp {
+    with_token(token, fetch).await
+}
+
 #[derive(Deserialize)]
 struct GradeReq {
-    report_period: Option<i32>,
+    // an index, a period name, a date inside the period or "current"
+    report_period: Option<String>,
 }
 
 async fn grades(token: AuthToken, req: Query<GradeReq>) -> Resp<gradebook::Response> {
     get_data(token, async |t: &mut AuthToken| {
-        return gradebook::get_grade_book(t, req.report_period).await;
+        let rp = gradebook::resolve_period(t, req.report_period.as_deref()).await?;
+        return gradebook::get_grade_book(t, rp).await;
+    })
+    .await
+}
+
+async fn missing(token: AuthToken, req: Query<GradeReq>) -> Resp<analytics::MissingReport> {
+    get_data(token, async |t: &mut AuthToken| {
+        let rp = gradebook::resolve_period(t, req.report_period.as_deref()).await?;
+        let gb = gradebook::cached_grade_book(t, rp).await?;
+        Ok(analytics::missing(&gb))
+    })
+    .await
+}
+
+async fn class_detail(
+    token: AuthToken,
+    Path(id): Path<String>,
+    req: Query<GradeReq>,
+) -> Resp<analytics::ClassDetail> {
+    get_data(token, async |t: &mut AuthToken| {
+        let rp = gradebook::resolve_period(t, req.report_period.as_deref()).await?;
+        let gb = gradebook::cached_grade_book(t, rp).await?;
+        analytics::class_detail(&gb, &id).ok_or(ApiError::NotFound)
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct AssignmentsReq {
+    // like GradeReq, but "all" searches every reporting period
+    report_period: Option<String>,
+    class: Option<String>,
+    kind: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    status: Option<gradebook::ScoreStatus>,
+    q: Option<String>,
+    sort: Option<assignments::Sort>,
+    #[serde(default)]
+    desc: bool,
+}
+
+async fn assignments(
+    token: AuthToken,
+    Query(req): Query<AssignmentsReq>,
+) -> Resp<Vec<assignments::AssignmentEntry>> {
+    let filter = assignments::Filter {
+        class: req.class,
+        kind: req.kind,
+        from: query_date(&req.from)?,
+        to: query_date(&req.to)?,
+        status: req.status,
+        text: req.q,
+        sort: req.sort,
+        descending: req.desc,
+    };
+
+    get_data(token, async |t: &mut AuthToken| {
+        let mut books = Vec::new();
+        if req.report_period.as_deref() == Some("all") {
+            let current = gradebook::cached_grade_book(t, None).await?;
+            let others: Vec<_> = stream::iter(0..current.reporting_periods.len() as i32)
+                .filter(|&rp| std::future::ready(rp != current.report_period))
+                .map(|rp| {
+                    let mut token = t.clone();
+                    async move {
+                        let book = gradebook::cached_grade_book(&mut token, Some(rp)).await?;
+                        Ok::<_, ApiError>((token, book))
+                    }
+                })
+                .buffered(4)
+                .try_collect()
+                .await?;
+
+            // keep whichever session cookie StudentVue handed out
+            if let Some((token, _)) = others.first() {
+                t.cookie = token.cookie.clone();
+            }
+            books.extend(others.into_iter().map(|(_, book)| book));
+            let at = (current.report_period.max(0) as usize).min(books.len());
+            books.insert(at, current);
+        } else {
+            let rp = gradebook::resolve_period(t, req.report_period.as_deref()).await?;
+            books.push(gradebook::cached_grade_book(t, rp).await?);
+        }
+
+        Ok(assignments::search(&books, &filter))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct CompareReq {
+    // default to the previous and current reporting period, there is no default `from` in the
+    // first period
+    from: Option<String>,
+    to: Option<String>,
+}
+
+async fn compare(token: AuthToken, Query(req): Query<CompareReq>) -> Resp<analytics::Comparison> {
+    get_data(token, async |t: &mut AuthToken| {
+        let current = gradebook::cached_grade_book(t, None).await?;
+        let from = match &req.from {
+            Some(p) => current.find_period(p)?,
+            // the first period has nothing before it to compare against
+            None if current.report_period > 0 => current.report_period - 1,
+            None => Err(ApiError::InvalidQuery)?,
+        };
+        let to = match &req.to {
+            Some(p) => current.find_period(p)?,
+            None => current.report_period,
+        };
+
+        let from = gradebook::cached_grade_book(t, Some(from)).await?;
+        let to = gradebook::cached_grade_book(t, Some(to)).await?;
+        Ok(analytics::compare(&from, &to))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct ProjectionReq {
+    // comma separated report periods, defaults to both periods of the current semester
+    periods: Option<String>,
+    method: Option<analytics::Method>,
+    // comma separated, one per period
+    weights: Option<String>,
+    exam_weight: Option<f32>,
+    exam: Option<f32>,
+}
+
+fn query_date(date: &Option<String>) -> Result<Option<time::Date>, ApiError> {
+    match date {
+        Some(d) => api::parse_date(d).map(Some).ok_or(ApiError::InvalidQuery),
+        None => Ok(None),
+    }
+}
+
+fn parse_list<T: std::str::FromStr>(list: &str) -> Result<Vec<T>, ApiError> {
+    list.split(',')
+        .map(|x| x.trim().parse().map_err(|_| ApiError::InvalidQuery))
+        .collect()
+}
+
+async fn projection(
+    token: AuthToken,
+    Query(req): Query<ProjectionReq>,
+) -> Resp<analytics::Projection> {
+    get_data(token, async |t: &mut AuthToken| {
+        let current = gradebook::cached_grade_book(t, None).await?;
+        let periods: Vec<i32> = match &req.periods {
+            Some(p) => p
+                .split(',')
+                .map(|p| current.find_period(p))
+                .collect::<Result<_, _>>()?,
+            // the current semester, assuming two periods to a semester
+            None => {
+                let first = current.report_period / 2 * 2;
+                let last = (first + 1).min(current.reporting_periods.len() as i32 - 1);
+                (first..=last.max(first)).collect()
+            }
+        };
+
+        let mut books = Vec::new();
+        for period in periods {
+            if period == current.report_period {
+                books.push(current.clone());
+            } else {
+                books.push(gradebook::cached_grade_book(t, Some(period)).await?);
+            }
+        }
+
+        // operators can set their district's policy, requests can still override it
+        let env = |key: &str| std::env::var(key).ok();
+        let method = req
+            .method
+            .or_else(|| env("GRADE_POLICY_METHOD").and_then(|m| m.parse().ok()))
+            .unwrap_or_default();
+        let exam_weight = req
+            .exam_weight
+            .or_else(|| env("GRADE_POLICY_EXAM_WEIGHT").and_then(|w| w.parse().ok()))
+            .unwrap_or(0.0);
+        if !(0.0..=1.0).contains(&exam_weight) {
+            Err(ApiError::InvalidQuery)?
+        }
+
+        // weights are fractions of the final grade, the periods split whatever the exam leaves
+        let weights: Vec<f32> = match &req.weights {
+            Some(w) => parse_list(w)?,
+            None => vec![(1.0 - exam_weight) / books.len() as f32; books.len()],
+        };
+
+        let sum: f32 = weights.iter().sum::<f32>() + exam_weight;
+        let negative = weights.iter().any(|w| *w < 0.0);
+        if weights.len() != books.len() || negative || (sum - 1.0).abs() > 0.01 {
+            Err(ApiError::InvalidQuery)?
+        }
+
+        let policy = analytics::Policy {
+            method,
+            weights,
+            exam_weight,
+        };
+
+        Ok(analytics::project(&books, &policy, req.exam))
     })
     .await
 }
@@ -104,95 +335,315 @@ async fn attendance(token: AuthToken) -> Resp<attendance::Response> {
     get_data(token, attendance::get_attendance).await
 }
 
-async fn documents(token: AuthToken) -> Resp<Vec<Document>> {
-    get_data(token, documents::list_documents).await
+#[derive(Deserialize)]
+struct DocsReq {
+    kind: Option<String>,
+    category: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
 }
 
-#[derive(Deserialize)]
-struct DocReq {
-    gu: String,
+async fn documents(token: AuthToken, Query(req): Query<DocsReq>) -> Resp<Vec<Document>> {
+    let filter = documents::DocumentFilter {
+        kind: req.kind,
+        category: req.category,
+        from: query_date(&req.from)?,
+        to: query_date(&req.to)?,
+    };
+
+    get_data(token, async |t: &mut AuthToken| {
+        let mut docs = documents::list_documents(t).await?;
+        docs.retain(|d| filter.matches(d));
+        Ok(docs)
+    })
+    .await
 }
 
-// needs old format :/
-async fn document(
-    mut token: AuthToken,
+// StudentVue only hands out a document's details along with its contents, so this downloads the
+// whole file. It's cached though, so opening the document right after is free.
+async fn document_info(
+    token: AuthToken,
     Query(dr): Query<DocReq>,
-) -> Result<(HeaderMap, Body), ApiError> {
-    if token.is_empty() {
-        Err(ApiError::EmptyCredentials)?
-    }
-    let old = token.clone();
-
-    let document = documents::get_document(&mut token, dr.gu).await?;
-    let mut headers = HeaderMap::new();
+) -> Resp<documents::DocumentData> {
+    get_data(token, async |t: &mut AuthToken| {
+        documents::cached_document(t, dr.gu).await
+    })
+    .await
+}
 
-    if old != token {
-        let enc = serde_json::to_string(&token).map_err(|_| ApiError::Unknown)?;
-        let tok = BASE64_STANDARD.encode(crypto::create_token(enc)?);
-        headers.insert(
-            HeaderName::from_static("Set-Token"),
-            HeaderValue::from_str(&tok).unwrap(),
-        );
-    }
+// streamed, so the documents are fetched while the archive is being sent
+async fn documents_archive(
+    token: AuthToken,
+) -> Result<(HeaderMap, (HeaderMap, Body)), ApiError> {
+    with_token(token, async |t: &mut AuthToken| {
+        let docs = documents::list_documents(t).await?;
 
-    if document.file_name.to_lowercase().ends_with(".pdf") {
-        let dep = format!("inline; filename=\"{}\"", document.file_name);
+        let mut headers = HeaderMap::new();
         headers.insert(
             header::CONTENT_TYPE,
-            HeaderValue::from_static("application/pdf"),
+            HeaderValue::from_static("application/zip"),
         );
         headers.insert(
             header::CONTENT_DISPOSITION,
-            HeaderValue::from_str(&dep).unwrap(),
+            HeaderValue::from_static("attachment; filename=\"documents.zip\""),
         );
-    } else {
-        let dep = format!("attachment; filename=\"{}\"", document.file_name);
-        headers.insert(
-            header::CONTENT_TYPE,
-            HeaderValue::from_static("application/octet-stream"),
-        );
-        headers.insert(
-            header::CONTENT_DISPOSITION,
-            HeaderValue::from_str(&dep).unwrap(),
-        );
-    };
 
-    Ok((headers, Body::from(document.file_data)))
+        let archive = documents::archive(t, docs).await?;
+        Ok((headers, Body::from_stream(ReaderStream::new(archive))))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct DocReq {
+    gu: String,
+}
+
+async fn document(token: AuthToken, headers: HeaderMap, Query(dr): Query<DocReq>) -> BinResp {
+    get_binary(token, async |t: &mut AuthToken| {
+        let document = documents::cached_document(t, dr.gu).await?;
+        Ok(Binary::new(document.file_name, document.file_data)
+            .with_range(headers.get(header::RANGE)))
+    })
+    .await
 }
 
 async fn student_info(token: AuthToken) -> Resp<StudentInfo> {
-    get_data(token, student_info::student_info).await
+    let profile = Profile::effective(&token);
+    get_data(token, async |t: &mut AuthToken| {
+        Ok(student_info::student_info(t).await?.redact(profile))
+    })
+    .await
+}
+
+async fn health(token: AuthToken) -> Resp<health::HealthInfo> {
+    // every record would be redacted, so don't download them at all
+    if Profile::effective(&token) > Profile::Full {
+        return Ok((HeaderMap::new(), Json(health::HealthInfo::default())));
+    }
+    get_data(token, health::health_info).await
+}
+
+async fn student_profile(
+    token: AuthToken,
+    Query(req): Query<PhotoReq>,
+) -> Resp<student_info::StudentProfile> {
+    let profile = Profile::effective(&token);
+    get_data(token, async |t: &mut AuthToken| {
+        let (info, photo) = student_info::both(t).await?;
+        let photo = match req.size {
+            Some(size) => student_info::thumbnail(t, size).await?,
+            None => photo,
+        };
+
+        Ok(student_info::StudentProfile {
+            info: info.redact(profile),
+            photo_type: media::sniff_image(&photo)
+                .unwrap_or("application/octet-stream")
+                .to_string(),
+            photo: BASE64_STANDARD.encode(photo),
+        })
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct ScopeReq {
+    profile: Profile,
 }
 
+#[derive(Serialize)]
+struct ScopedToken {
+    token: String,
+}
 
-// old format for this too
-async fn student_photo(mut token: AuthToken) -> Result<(HeaderMap, Body), ApiError> {
+// hands out a copy of the token restricted to a redaction profile, e.g. for a tutor's screen
+async fn scope(
+    mut token: AuthToken,
+    Query(req): Query<ScopeReq>,
+) -> Result<Json<ScopedToken>, ApiError> {
     if token.is_empty() {
         Err(ApiError::EmptyCredentials)?
     }
-    let old = token.clone();
 
-    let bytes = student_info::photo(&mut token).await?;
-    let mut headers = HeaderMap::new();
+    // scopes only ever narrow
+    token.profile = token.profile.max(req.profile);
+    Ok(Json(ScopedToken {
+        token: token.encode()?,
+    }))
+}
 
-    if old != token {
-        let enc = serde_json::to_string(&token).map_err(|_| ApiError::Unknown)?;
-        let tok = BASE64_STANDARD.encode(crypto::create_token(enc)?);
-        headers.insert(
-            HeaderName::from_static("Set-Token"),
-            HeaderValue::from_str(&tok).unwrap(),
-        );
+async fn children(token: AuthToken) -> Resp<Vec<children::Child>> {
+    if !token.parent {
+        Err(ApiError::NotFound)?
     }
+    get_data(token, children::list_children).await
+}
+
+#[derive(Deserialize)]
+struct ChildReq {
+    id: u32,
+}
 
-    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("image/png"));
-    headers.insert(
-        header::CONTENT_DISPOSITION,
-        HeaderValue::from_static("attachment; filename=\"image.png\""),
-    );
+// hands out a copy of the token that always acts on behalf of one child
+async fn select_child(
+    mut token: AuthToken,
+    Query(req): Query<ChildReq>,
+) -> Result<Json<ScopedToken>, ApiError> {
+    if token.is_empty() {
+        Err(ApiError::EmptyCredentials)?
+    }
+    if !token.parent {
+        Err(ApiError::NotFound)?
+    }
 
-    Ok((headers, Body::from(bytes)))
+    // the new token carries whatever cookie StudentVue handed out here
+    let children = children::list_children(&mut token).await?;
+    if !children.iter().any(|c| c.id == req.id) {
+        Err(ApiError::NotFound)?
+    }
+
+    token.child = Some(req.id);
+    Ok(Json(ScopedToken {
+        token: token.encode()?,
+    }))
+}
+
+#[derive(Deserialize)]
+struct PhotoReq {
+    // longest side of a thumbnail, in pixels, rounded up to 32, 64, 128, 256 or 512
+    size: Option<u32>,
 }
 
+async fn student_photo(token: AuthToken, Query(req): Query<PhotoReq>) -> BinResp {
+    get_binary(token, async |t: &mut AuthToken| {
+        let bytes = match req.size {
+            Some(size) => student_info::thumbnail(t, size).await?,
+            None => student_info::photo(t).await?,
+        };
+
+        let mime = media::sniff_image(&bytes).unwrap_or("application/octet-stream");
+        let name = format!("image.{}", media::image_extension(mime));
+        Ok(Binary::new(name, bytes))
+    })
+    .await
+}
+
+async fn messages(token: AuthToken) -> Resp<Vec<messages::Message>> {
+    get_data(token, messages::list_messages).await
+}
+
+#[derive(Deserialize)]
+struct ReadReq {
+    id: String,
+    kind: String,
+}
+
+#[derive(Serialize)]
+struct ReadResp {
+    id: String,
+    read: bool,
+}
+
+async fn mark_read(token: AuthToken, Json(req): Json<ReadReq>) -> Resp<ReadResp> {
+    get_data(token, async |t: &mut AuthToken| {
+        messages::mark_read(t, &req.id, &req.kind).await?;
+        Ok(ReadResp {
+            id: req.id,
+            read: true,
+        })
+    })
+    .await
+}
+
+async fn message_attachment(
+    token: AuthToken,
+    headers: HeaderMap,
+    Query(dr): Query<DocReq>,
+) -> BinResp {
+    get_binary(token, async |t: &mut AuthToken| {
+        let attachment = messages::get_attachment(t, &dr.gu).await?;
+        Ok(Binary::new(attachment.file_name, attachment.file_data)
+            .with_range(headers.get(header::RANGE)))
+    })
+    .await
+}
+
+async fn course_history(token: AuthToken) -> Resp<course_history::CourseHistory> {
+    get_data(token, course_history::course_history).await
+}
+
+#[derive(Deserialize)]
+struct NotesReq {
+    // a gradebook class id, to only get that class' notes
+    course: Option<String>,
+}
+
+async fn notes(token: AuthToken, Query(req): Query<NotesReq>) -> Resp<Vec<notes::CourseNotes>> {
+    get_data(token, async |t: &mut AuthToken| {
+        let gb = gradebook::cached_grade_book(t, None).await?;
+        let mut courses = notes::list_notes(t, &gb.classes).await?;
+        if let Some(course) = &req.course {
+            courses.retain(|c| &c.id == course);
+        }
+        Ok(courses)
+    })
+    .await
+}
+
+async fn test_history(token: AuthToken) -> Resp<Vec<assessments::Test>> {
+    get_data(token, assessments::test_history).await
+}
+
+async fn fees(token: AuthToken) -> Resp<fees::Fees> {
+    get_data(token, fees::fees).await
+}
+
+async fn report_cards(token: AuthToken) -> Resp<Vec<report_cards::ReportCard>> {
+    get_data(token, report_cards::list_report_cards).await
+}
+
+async fn report_card(token: AuthToken, headers: HeaderMap, Query(dr): Query<DocReq>) -> BinResp {
+    get_binary(token, async |t: &mut AuthToken| {
+        let report_card = report_cards::cached_report_card(t, dr.gu).await?;
+        Ok(Binary::new(report_card.file_name, report_card.file_data)
+            .with_range(headers.get(header::RANGE)))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct CalendarReq {
+    // YYYY-MM, defaults to the current month
+    month: Option<String>,
+    // or an explicit date range
+    from: Option<String>,
+    to: Option<String>,
+}
+
+async fn calendar(token: AuthToken, Query(req): Query<CalendarReq>) -> Resp<calendar::Calendar> {
+    let (from, to) = match (&req.month, query_date(&req.from)?, query_date(&req.to)?) {
+        (None, Some(from), Some(to)) => (from, to),
+        (month, None, None) => {
+            let first = match month {
+                Some(m) => api::parse_date(&format!("{m}-01")).ok_or(ApiError::InvalidQuery)?,
+                None => time::OffsetDateTime::now_utc().date().replace_day(1).unwrap(),
+            };
+            let last = first
+                .replace_day(first.month().length(first.year()))
+                .map_err(|_| ApiError::InvalidQuery)?;
+            (first, last)
+        }
+        _ => Err(ApiError::InvalidQuery)?,
+    };
+
+    // a school year, give or take
+    if to < from || (to - from).whole_days() > 400 {
+        Err(ApiError::InvalidQuery)?
+    }
+
+    get_data(token, async |t: &mut AuthToken| calendar::calendar(t, from, to).await).await
+}
 
 async fn school_info(token: AuthToken) -> Resp<SchoolInfo> {
     get_data(token, school_info::school_info).await
@@ -219,11 +670,33 @@ async fn schedule(
 pub async fn main() {
     let mut router = AxumRouter::new()
         .route("/grades", get(grades))
+        .route("/grades/missing", get(missing))
+        .route("/grades/class/{id}", get(class_detail))
+        .route("/grades/projection", get(projection))
+        .route("/grades/compare", get(compare))
+        .route("/assignments", get(assignments))
         .route("/documents", get(documents))
+        .route("/documents/archive", get(documents_archive))
         .route("/document", get(document))
+        .route("/document/info", get(document_info))
         .route("/student", get(student_info))
+        .route("/student/full", get(student_profile))
         .route("/photo", get(student_photo))
-        .route("/school", get(school_info)); 
+        .route("/school", get(school_info))
+        .route("/messages", get(messages))
+        .route("/messages/read", post(mark_read))
+        .route("/messages/attachment", get(message_attachment))
+        .route("/calendar", get(calendar))
+        .route("/reportcards", get(report_cards))
+        .route("/reportcard", get(report_card))
+        .route("/history", get(course_history))
+        .route("/health", get(health))
+        .route("/notes", get(notes))
+        .route("/tests", get(test_history))
+        .route("/fees", get(fees))
+        .route("/children", get(children))
+        .route("/children/select", get(select_child))
+        .route("/token", get(scope)); 
         
     #[cfg(feature = "schedule")]
     {
@@ -240,8 +713,17 @@ pub async fn main() {
         router = router.merge(advanced::ext());
     }
 
-    router = router.layer(CorsLayer::very_permissive().expose_headers([HeaderName::from_static("set-token")]))
-        .layer(CompressionLayer::new().br(true));
+    // files are sent as is so viewers can seek, and browsers need to see the range headers
+    let compress = DefaultPredicate::new()
+        .and(|_, _, headers: &HeaderMap, _: &axum::http::Extensions| media::compressible(headers));
+    router = router
+        .layer(CorsLayer::very_permissive().expose_headers([
+            HeaderName::from_static("set-token"),
+            header::CONTENT_RANGE,
+            header::ACCEPT_RANGES,
+            header::CONTENT_LENGTH,
+        ]))
+        .layer(CompressionLayer::new().br(true).compress_when(compress));
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:2727").await.unwrap();
     axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();